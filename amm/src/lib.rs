@@ -0,0 +1,359 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+/// This module provides constant-product (x * y = k) pools between any two assets.
+/// Each pool keeps its reserves in a module derived account, liquidity providers get the
+/// pool's LP asset in return, which is created as a reserved asset of the assets module.
+/// Swaps pay a fee in the input asset to "ProfitPool".
+/// The first deposit of a pool locks "MINIMUM_LIQUIDITY" LP tokens in the pool account for good.
+use codec::{Decode, Encode};
+use rstd::prelude::*;
+use sp_runtime::{
+    traits::{AccountIdConversion, One, SimpleArithmetic, Zero},
+    ModuleId,
+};
+use support::{
+    decl_event, decl_module, decl_storage,
+    dispatch::Result as DispatchResult,
+    ensure,
+    traits::EnsureOrigin,
+    weights::SimpleDispatchInfo,
+};
+use system::ensure_signed;
+
+use assets::{AssetOptions, PermissionLatest};
+
+mod mock;
+mod tests;
+
+pub const MODULE_ID: ModuleId = ModuleId(*b"dfx/amm_");
+pub const FEE_PREC: u32 = 10000;
+/// LP tokens locked by the first deposit, so the LP supply can't be brought down to a few tokens worth a lot each
+pub const MINIMUM_LIQUIDITY: u32 = 1000;
+
+pub type PoolId = u32;
+
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Pool<AssetId, Balance> {
+    pub id: PoolId,
+    pub asset_a: AssetId,
+    pub asset_b: AssetId,
+    pub lp_asset_id: AssetId,
+    pub reserve_a: Balance,
+    pub reserve_b: Balance,
+}
+
+impl<AssetId, Balance> Pool<AssetId, Balance>
+where
+    AssetId: PartialEq + Copy,
+    Balance: SimpleArithmetic + Copy,
+{
+    /// (reserve of the input asset, reserve of the output asset, output asset)
+    pub fn reserves_for(&self, asset_in: AssetId) -> Option<(Balance, Balance, AssetId)> {
+        if asset_in == self.asset_a {
+            Some((self.reserve_a, self.reserve_b, self.asset_b))
+        } else if asset_in == self.asset_b {
+            Some((self.reserve_b, self.reserve_a, self.asset_a))
+        } else {
+            None
+        }
+    }
+
+    /// how much of the output asset "amount_in" gets, after the fee is taken, None if it overflows
+    pub fn get_amount_out(reserve_in: Balance, reserve_out: Balance, amount_in: Balance) -> Option<Balance> {
+        Some(amount_in.checked_mul(&reserve_out)? / reserve_in.checked_add(&amount_in)?)
+    }
+}
+
+pub trait Trait: assets::Trait {
+    type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+    /// the origin that creates pools and sets the fee, e.g. a collective, a multisig or sudo
+    type AdminOrigin: EnsureOrigin<Self::Origin>;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as Amm {
+        /// where swap fees go
+        ProfitPool get(profit_pool) config() : T::AccountId;
+        /// swap fee in 1/10000 of the input amount
+        SwapFee get(swap_fee) config() : u32;
+
+        NextPoolId get(next_pool_id) : PoolId;
+        pub Pools get(pool) : map PoolId => Option<Pool<T::AssetId, T::Balance>>;
+        /// (smaller asset id, bigger asset id) => pool, at most one pool per pair
+        pub PoolByAssets get(pool_by_assets) : map (T::AssetId, T::AssetId) => Option<PoolId>;
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        fn deposit_event() = default;
+
+        /// create a pool of two assets, the LP asset is created as "lp_asset_id" with "lp_symbol" through assets::create_reserved
+        /// the origin is passed on to assets::create_reserved and has to be the assets admin as well
+        /// *admin only*
+        #[weight = SimpleDispatchInfo::FreeOperational]
+        pub fn create_pool(origin, asset_a: T::AssetId, asset_b: T::AssetId, lp_asset_id: T::AssetId, lp_symbol: Vec<u8>) -> DispatchResult {
+            <T as Trait>::AdminOrigin::ensure_origin(origin.clone())?;
+            ensure!(asset_a != asset_b, "can't pool an asset with itself");
+            ensure!(lp_asset_id != asset_a && lp_asset_id != asset_b, "invalid LP asset");
+            ensure!(
+                <assets::Module<T>>::asset_exists(&asset_a) && <assets::Module<T>>::asset_exists(&asset_b),
+                "asset doesn't exist"
+            );
+            let pair = Self::pair_of(asset_a, asset_b);
+            ensure!(!<PoolByAssets<T>>::exists(&pair), "pool already exists");
+
+            // LP tokens are minted and burnt by the assets module account
+            let account = <assets::Module<T>>::account_id();
+            let options = AssetOptions {
+                initial_issuance: Zero::zero(),
+                permissions: PermissionLatest {
+                    update: pallet_generic_asset::Owner::Address(account.clone()),
+                    mint: pallet_generic_asset::Owner::Address(account.clone()),
+                    burn: pallet_generic_asset::Owner::Address(account),
+                },
+            };
+            <assets::Module<T>>::create_reserved(origin, lp_asset_id, options, lp_symbol)?;
+
+            let id = Self::next_pool_id();
+            NextPoolId::put(id + 1);
+            <Pools<T>>::insert(id, Pool {
+                id,
+                asset_a,
+                asset_b,
+                lp_asset_id,
+                reserve_a: Zero::zero(),
+                reserve_b: Zero::zero(),
+            });
+            <PoolByAssets<T>>::insert(&pair, id);
+            Self::deposit_event(RawEvent::PoolCreated(id, asset_a, asset_b, lp_asset_id));
+            Ok(())
+        }
+
+        /// *admin only*
+        #[weight = SimpleDispatchInfo::FreeOperational]
+        pub fn set_swap_fee(origin, fee: u32) -> DispatchResult {
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
+            ensure!(fee < FEE_PREC, "invalid fee");
+            SwapFee::put(fee);
+            Ok(())
+        }
+
+        /// deposit "amount_a" of asset a and the matching amount of asset b, at most "max_amount_b"
+        /// the first deposit sets the price, and gets as many LP tokens as "amount_a" less "MINIMUM_LIQUIDITY"
+        #[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+        pub fn add_liquidity(origin, pool_id: PoolId, amount_a: T::Balance, max_amount_b: T::Balance, min_lp: T::Balance) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(!amount_a.is_zero(), "zero is not allowed");
+            let mut pool = Self::pool(pool_id).ok_or("pool doesn't exist")?;
+            let supply = <assets::Module<T>>::total_issuance(&pool.lp_asset_id);
+
+            let (amount_b, lp, locked) = if supply.is_zero() {
+                let locked = T::Balance::from(MINIMUM_LIQUIDITY);
+                ensure!(amount_a > locked, "first deposit is below the minimum liquidity");
+                (max_amount_b, amount_a - locked, locked)
+            } else {
+                // round up in favor of the pool
+                let amount_b = Self::mul_div(amount_a, pool.reserve_b, pool.reserve_a, true)?;
+                (amount_b, Self::mul_div(amount_a, supply, pool.reserve_a, false)?, Zero::zero())
+            };
+            ensure!(!amount_b.is_zero() && amount_b <= max_amount_b, "exceed max amount of asset b");
+            ensure!(!lp.is_zero() && lp >= min_lp, "slippage exceeds the limit");
+            let reserve_a = pool.reserve_a.checked_add(&amount_a).ok_or("amount overflows")?;
+            let reserve_b = pool.reserve_b.checked_add(&amount_b).ok_or("amount overflows")?;
+
+            let account = Self::pool_account(pool_id);
+            <assets::Module<T>>::make_transfer_with_event(&pool.asset_a, &who, &account, amount_a)?;
+            <assets::Module<T>>::make_transfer_with_event(&pool.asset_b, &who, &account, amount_b)
+                .or_else(|err| -> DispatchResult {
+                    <assets::Module<T>>::make_transfer_with_event(&pool.asset_a, &account, &who, amount_a)?;
+                    Err(err)
+                })?;
+            <assets::Module<T>>::make_mint(&pool.lp_asset_id, &who, lp).or_else(|err| -> DispatchResult {
+                <assets::Module<T>>::make_transfer_with_event(&pool.asset_a, &account, &who, amount_a)?;
+                <assets::Module<T>>::make_transfer_with_event(&pool.asset_b, &account, &who, amount_b)?;
+                Err(err)
+            })?;
+            if !locked.is_zero() {
+                <assets::Module<T>>::make_mint(&pool.lp_asset_id, &account, locked).or_else(|err| -> DispatchResult {
+                    <assets::Module<T>>::make_burn(&pool.lp_asset_id, &who, lp)?;
+                    <assets::Module<T>>::make_transfer_with_event(&pool.asset_a, &account, &who, amount_a)?;
+                    <assets::Module<T>>::make_transfer_with_event(&pool.asset_b, &account, &who, amount_b)?;
+                    Err(err)
+                })?;
+            }
+
+            pool.reserve_a = reserve_a;
+            pool.reserve_b = reserve_b;
+            <Pools<T>>::insert(pool_id, pool);
+            Self::deposit_event(RawEvent::LiquidityAdded(pool_id, who, amount_a, amount_b, lp));
+            Ok(())
+        }
+
+        /// burn "lp" LP tokens for the share of both reserves
+        #[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+        pub fn remove_liquidity(origin, pool_id: PoolId, lp: T::Balance, min_amount_a: T::Balance, min_amount_b: T::Balance) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(!lp.is_zero(), "zero is not allowed");
+            let mut pool = Self::pool(pool_id).ok_or("pool doesn't exist")?;
+            let supply = <assets::Module<T>>::total_issuance(&pool.lp_asset_id);
+            ensure!(lp <= supply, "not enough LP tokens");
+
+            let amount_a = Self::mul_div(lp, pool.reserve_a, supply, false)?;
+            let amount_b = Self::mul_div(lp, pool.reserve_b, supply, false)?;
+            ensure!(amount_a >= min_amount_a && amount_b >= min_amount_b, "slippage exceeds the limit");
+
+            let account = Self::pool_account(pool_id);
+            <assets::Module<T>>::make_burn(&pool.lp_asset_id, &who, lp)?;
+            <assets::Module<T>>::make_transfer_with_event(&pool.asset_a, &account, &who, amount_a)
+                .or_else(|err| -> DispatchResult {
+                    <assets::Module<T>>::make_mint(&pool.lp_asset_id, &who, lp)?;
+                    Err(err)
+                })?;
+            <assets::Module<T>>::make_transfer_with_event(&pool.asset_b, &account, &who, amount_b)
+                .or_else(|err| -> DispatchResult {
+                    <assets::Module<T>>::make_transfer_with_event(&pool.asset_a, &who, &account, amount_a)?;
+                    <assets::Module<T>>::make_mint(&pool.lp_asset_id, &who, lp)?;
+                    Err(err)
+                })?;
+
+            pool.reserve_a -= amount_a;
+            pool.reserve_b -= amount_b;
+            <Pools<T>>::insert(pool_id, pool);
+            Self::deposit_event(RawEvent::LiquidityRemoved(pool_id, who, amount_a, amount_b, lp));
+            Ok(())
+        }
+
+        /// sell "amount_in" of "asset_in" for at least "min_amount_out" of the other asset of the pool
+        #[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+        pub fn swap(origin, pool_id: PoolId, asset_in: T::AssetId, amount_in: T::Balance, min_amount_out: T::Balance) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::make_swap(&who, pool_id, asset_in, amount_in, min_amount_out)?;
+            Ok(())
+        }
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as system::Trait>::AccountId,
+        AssetId = <T as pallet_generic_asset::Trait>::AssetId,
+        Balance = <T as pallet_generic_asset::Trait>::Balance,
+    {
+        /// (pool id, asset a, asset b, LP asset)
+        PoolCreated(PoolId, AssetId, AssetId, AssetId),
+        /// (pool id, provider, amount a, amount b, LP tokens)
+        LiquidityAdded(PoolId, AccountId, Balance, Balance, Balance),
+        LiquidityRemoved(PoolId, AccountId, Balance, Balance, Balance),
+        /// (pool id, trader, asset in, amount in, asset out, amount out, fee)
+        Swapped(PoolId, AccountId, AssetId, Balance, AssetId, Balance, Balance),
+    }
+);
+
+impl<T: Trait> Module<T> {
+    /// the account holding the reserves of a pool
+    pub fn pool_account(pool_id: PoolId) -> T::AccountId {
+        MODULE_ID.into_sub_account(pool_id)
+    }
+
+    fn pair_of(a: T::AssetId, b: T::AssetId) -> (T::AssetId, T::AssetId) {
+        if a < b {
+            (a, b)
+        } else {
+            (b, a)
+        }
+    }
+
+    /// how much "amount_in" of "asset_in" gets from the pool right now, after the fee
+    pub fn quote(pool_id: PoolId, asset_in: T::AssetId, amount_in: T::Balance) -> Option<T::Balance> {
+        let pool = Self::pool(pool_id)?;
+        let (reserve_in, reserve_out, _) = pool.reserves_for(asset_in)?;
+        let fee = Self::fee_of(amount_in);
+        Pool::<T::AssetId, T::Balance>::get_amount_out(reserve_in, reserve_out, amount_in - fee)
+    }
+
+    /// divided first, so it can't overflow
+    fn fee_of(amount: T::Balance) -> T::Balance {
+        let prec = T::Balance::from(FEE_PREC);
+        let fee = T::Balance::from(Self::swap_fee());
+        amount / prec * fee + amount % prec * fee / prec
+    }
+
+    /// a * b / c, rounded up if "round_up"
+    fn mul_div(a: T::Balance, b: T::Balance, c: T::Balance, round_up: bool) -> Result<T::Balance, &'static str> {
+        ensure!(!c.is_zero(), "pool is empty");
+        let mut product = a.checked_mul(&b).ok_or("amount overflows")?;
+        if round_up {
+            product = product.checked_add(&(c - One::one())).ok_or("amount overflows")?;
+        }
+        Ok(product / c)
+    }
+
+    /// returns the amount of the output asset "who" gets
+    /// for other modules to trade against the pools, e.g. liquidation
+    pub fn make_swap(
+        who: &T::AccountId,
+        pool_id: PoolId,
+        asset_in: T::AssetId,
+        amount_in: T::Balance,
+        min_amount_out: T::Balance,
+    ) -> Result<T::Balance, &'static str> {
+        ensure!(!amount_in.is_zero(), "zero is not allowed");
+        let mut pool = Self::pool(pool_id).ok_or("pool doesn't exist")?;
+        let (reserve_in, reserve_out, asset_out) =
+            pool.reserves_for(asset_in).ok_or("asset is not in the pool")?;
+        ensure!(!reserve_in.is_zero() && !reserve_out.is_zero(), "pool is empty");
+
+        let fee = Self::fee_of(amount_in);
+        let net = amount_in - fee;
+        let amount_out = Pool::<T::AssetId, T::Balance>::get_amount_out(reserve_in, reserve_out, net)
+            .ok_or("amount overflows")?;
+        ensure!(!amount_out.is_zero(), "amount is too small");
+        ensure!(amount_out >= min_amount_out, "slippage exceeds the limit");
+
+        let account = Self::pool_account(pool_id);
+        let profit_pool = Self::profit_pool();
+        if !fee.is_zero() {
+            <assets::Module<T>>::make_transfer_with_event(&asset_in, who, &profit_pool, fee)?;
+        }
+        <assets::Module<T>>::make_transfer_with_event(&asset_in, who, &account, net).or_else(
+            |err| -> DispatchResult {
+                if !fee.is_zero() {
+                    <assets::Module<T>>::make_transfer_with_event(&asset_in, &profit_pool, who, fee)?;
+                }
+                Err(err)
+            },
+        )?;
+        <assets::Module<T>>::make_transfer_with_event(&asset_out, &account, who, amount_out).or_else(
+            |err| -> DispatchResult {
+                <assets::Module<T>>::make_transfer_with_event(&asset_in, &account, who, net)?;
+                if !fee.is_zero() {
+                    <assets::Module<T>>::make_transfer_with_event(&asset_in, &profit_pool, who, fee)?;
+                }
+                Err(err)
+            },
+        )?;
+
+        if asset_in == pool.asset_a {
+            pool.reserve_a += net;
+            pool.reserve_b -= amount_out;
+        } else {
+            pool.reserve_b += net;
+            pool.reserve_a -= amount_out;
+        }
+        <Pools<T>>::insert(pool_id, pool);
+        Self::deposit_event(RawEvent::Swapped(
+            pool_id,
+            who.clone(),
+            asset_in,
+            amount_in,
+            asset_out,
+            amount_out,
+            fee,
+        ));
+        Ok(amount_out)
+    }
+}
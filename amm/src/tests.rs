@@ -0,0 +1,133 @@
+#![cfg(test)]
+
+use super::*;
+
+use runtime_io::with_externalities;
+use support::{assert_noop, assert_ok};
+
+use mock::*;
+
+fn create_pool() -> PoolId {
+    let pool_id = Amm::next_pool_id();
+    assert_ok!(Amm::create_pool(Origin::ROOT, SBTC_ASSET_ID, TBD_ASSET_ID, LP_ASSET_ID, b"LP".to_vec()));
+    assert_ok!(Assets::mint(Origin::ROOT, SBTC_ASSET_ID, ALICE, 1_000_000));
+    assert_ok!(Assets::mint(Origin::ROOT, TBD_ASSET_ID, ALICE, 100_000_000));
+    pool_id
+}
+
+#[test]
+fn liquidity_works() {
+    with_externalities(&mut new_test_ext(), || {
+        assert!(
+            Amm::create_pool(Origin::signed(ALICE), SBTC_ASSET_ID, TBD_ASSET_ID, LP_ASSET_ID, b"LP".to_vec())
+                .is_err()
+        );
+        let pool_id = create_pool();
+        assert_eq!(Assets::owner(&LP_ASSET_ID), Some(Assets::account_id()));
+        assert_noop!(
+            Amm::create_pool(Origin::ROOT, TBD_ASSET_ID, SBTC_ASSET_ID, 4, b"LP2".to_vec()),
+            "pool already exists"
+        );
+
+        assert_noop!(
+            Amm::add_liquidity(Origin::signed(ALICE), pool_id, 1_000, 100_000, 0),
+            "first deposit is below the minimum liquidity"
+        );
+        // 1 SBTC = 100 TBD, 1_000 of the LP tokens are locked in the pool account
+        assert_ok!(Amm::add_liquidity(Origin::signed(ALICE), pool_id, 10_000, 1_000_000, 0));
+        assert_eq!(Assets::free_balance(&LP_ASSET_ID, &ALICE), 9_000);
+        assert_eq!(Assets::free_balance(&LP_ASSET_ID, &Amm::pool_account(pool_id)), 1_000);
+        assert_eq!(
+            Assets::free_balance(&TBD_ASSET_ID, &Amm::pool_account(pool_id)),
+            1_000_000
+        );
+
+        assert_noop!(
+            Amm::add_liquidity(Origin::signed(ALICE), pool_id, 5_000, 499_999, 0),
+            "exceed max amount of asset b"
+        );
+        assert_ok!(Amm::add_liquidity(Origin::signed(ALICE), pool_id, 5_000, 500_000, 5_000));
+        assert_eq!(Assets::free_balance(&LP_ASSET_ID, &ALICE), 14_000);
+
+        assert_noop!(
+            Amm::remove_liquidity(Origin::signed(ALICE), pool_id, 3_000, 3_001, 0),
+            "slippage exceeds the limit"
+        );
+        assert_ok!(Amm::remove_liquidity(
+            Origin::signed(ALICE),
+            pool_id,
+            3_000,
+            3_000,
+            300_000
+        ));
+        let pool = Amm::pool(pool_id).unwrap();
+        assert_eq!((pool.reserve_a, pool.reserve_b), (12_000, 1_200_000));
+        assert_eq!(Assets::total_issuance(&LP_ASSET_ID), 12_000);
+
+        assert_noop!(
+            Amm::add_liquidity(Origin::signed(BOB), pool_id, u128::max_value() / 100, u128::max_value(), 0),
+            "amount overflows"
+        );
+    });
+}
+
+#[test]
+fn swap_works() {
+    with_externalities(&mut new_test_ext(), || {
+        let pool_id = create_pool();
+        assert_ok!(Amm::add_liquidity(Origin::signed(ALICE), pool_id, 100_000, 10_000_000, 0));
+        assert_ok!(Assets::mint(Origin::ROOT, SBTC_ASSET_ID, BOB, 10_000));
+
+        // 30 of the 10_000 in is the fee, 9_970 * 10_000_000 / 109_970
+        let quoted = Amm::quote(pool_id, SBTC_ASSET_ID, 10_000).unwrap();
+        assert_eq!(quoted, 906_610);
+        assert_noop!(
+            Amm::swap(Origin::signed(BOB), pool_id, SBTC_ASSET_ID, 10_000, quoted + 1),
+            "slippage exceeds the limit"
+        );
+        assert_ok!(Amm::swap(Origin::signed(BOB), pool_id, SBTC_ASSET_ID, 10_000, quoted));
+        assert_eq!(Assets::free_balance(&SBTC_ASSET_ID, &BOB), 0);
+        assert_eq!(Assets::free_balance(&TBD_ASSET_ID, &BOB), quoted);
+        assert_eq!(Assets::free_balance(&SBTC_ASSET_ID, &PROFIT_POOL), 30);
+
+        let pool = Amm::pool(pool_id).unwrap();
+        assert_eq!((pool.reserve_a, pool.reserve_b), (109_970, 10_000_000 - quoted));
+        assert_eq!(Amm::quote(pool_id, SBTC_ASSET_ID, u128::max_value()), None);
+
+        assert_noop!(
+            Amm::swap(Origin::signed(BOB), pool_id, LP_ASSET_ID, 1, 0),
+            "asset is not in the pool"
+        );
+    });
+}
+
+#[test]
+fn failed_legs_are_reverted() {
+    with_externalities(&mut new_test_ext(), || {
+        let pool_id = create_pool();
+        let account = Amm::pool_account(pool_id);
+        assert_ok!(Amm::add_liquidity(Origin::signed(ALICE), pool_id, 100_000, 10_000_000, 0));
+        assert_ok!(Assets::mint(Origin::ROOT, SBTC_ASSET_ID, BOB, 10_000));
+        // the pool can't pay out TBD
+        assert_ok!(Assets::freeze(Origin::ROOT, TBD_ASSET_ID, account));
+
+        assert_eq!(
+            Amm::remove_liquidity(Origin::signed(ALICE), pool_id, 3_000, 0, 0),
+            Err("account is frozen for this asset")
+        );
+        assert_eq!(Assets::free_balance(&LP_ASSET_ID, &ALICE), 99_000);
+        assert_eq!(Assets::free_balance(&SBTC_ASSET_ID, &ALICE), 900_000);
+        assert_eq!(Assets::free_balance(&SBTC_ASSET_ID, &account), 100_000);
+        assert_eq!(Assets::total_issuance(&LP_ASSET_ID), 100_000);
+
+        assert_eq!(
+            Amm::swap(Origin::signed(BOB), pool_id, SBTC_ASSET_ID, 10_000, 0),
+            Err("account is frozen for this asset")
+        );
+        assert_eq!(Assets::free_balance(&SBTC_ASSET_ID, &BOB), 10_000);
+        assert_eq!(Assets::free_balance(&SBTC_ASSET_ID, &PROFIT_POOL), 0);
+        assert_eq!(Assets::free_balance(&SBTC_ASSET_ID, &account), 100_000);
+        let pool = Amm::pool(pool_id).unwrap();
+        assert_eq!((pool.reserve_a, pool.reserve_b), (100_000, 10_000_000));
+    });
+}
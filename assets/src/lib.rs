@@ -2,18 +2,29 @@
 
 /// This module extends the pallet-generic-asset module.
 /// With an extra asset symbol for each asset.
-/// Currently we are using a very simple management model, the pallet-sudo,
-/// the more decentralized strategy is incoming.
+/// Assets are administrated by T::AdminOrigin, which can be a collective, a multisig or sudo,
+/// the assets it creates are owned by the module account.
 mod mock;
 mod tests;
 
-use rstd::{result, vec::Vec};
+use codec::{Decode, Encode};
+use rstd::{cmp, result, vec::Vec};
 use support::{
-    decl_event, decl_module, decl_storage, dispatch::Result, ensure, weights::SimpleDispatchInfo,
+    decl_event, decl_module, decl_storage,
+    dispatch::Result,
+    ensure,
+    traits::{Currency, EnsureOrigin, Get, ReservableCurrency},
+    weights::{ClassifyDispatch, DispatchClass, PaysFee, SimpleDispatchInfo, WeighData, Weight},
 };
-use system::{ensure_root, ensure_signed, Error};
+use system::ensure_signed;
 
-use sp_runtime::traits::Dispatchable;
+use sp_runtime::{
+    traits::{
+        AccountIdConversion, CheckedAdd, CheckedSub, Dispatchable, SaturatedConversion, Saturating,
+        SimpleArithmetic, One, Zero,
+    },
+    ModuleId,
+};
 
 pub use generic_asset::AssetOptions;
 pub use generic_asset::PermissionLatest;
@@ -76,21 +87,341 @@ pub mod traits {
         }
     }
 
+    pub trait BeforeAssetDestroy<AssetId> {
+        fn before_asset_destroy(_asset_id: &AssetId) -> Result {
+            Ok(())
+        }
+    }
+
+    pub trait OnAssetReaped<AssetId, AccountId> {
+        fn on_asset_reaped(_asset_id: &AssetId, _who: &AccountId) -> Result {
+            Ok(())
+        }
+    }
+
     impl<A> BeforeAssetCreate<A> for () {}
     impl<A> OnAssetCreate<A> for () {}
-    impl<A, B, C> BeforeAssetBurn<A, B, C> for () {}
     impl<A, B, C> OnAssetBurn<A, B, C> for () {}
-    impl<A, B, C> BeforeAssetMint<A, B, C> for () {}
     impl<A, B, C> OnAssetMint<A, B, C> for () {}
-    impl<A, B, C> BeforeAssetTransfer<A, B, C> for () {}
     impl<A, B, C> OnAssetTransfer<A, B, C> for () {}
+
+    // the gating hooks can be stacked, e.g. "(saving::Module<T>, compliance::Module<T>)"
+    // every member must agree, in order, "()" agrees on everything
+    #[impl_trait_for_tuples::impl_for_tuples(5)]
+    impl<A, B, C> BeforeAssetBurn<A, B, C> for Tuple {
+        fn before_asset_burn(asset_id: &A, to: &B, balance: &C) -> Result {
+            for_tuples!( #( Tuple::before_asset_burn(asset_id, to, balance)?; )* );
+            Ok(())
+        }
+    }
+    #[impl_trait_for_tuples::impl_for_tuples(5)]
+    impl<A, B, C> BeforeAssetMint<A, B, C> for Tuple {
+        fn before_asset_mint(asset_id: &A, to: &B, balance: &C) -> Result {
+            for_tuples!( #( Tuple::before_asset_mint(asset_id, to, balance)?; )* );
+            Ok(())
+        }
+    }
+    #[impl_trait_for_tuples::impl_for_tuples(5)]
+    impl<A, B, C> BeforeAssetTransfer<A, B, C> for Tuple {
+        fn before_asset_transfer(asset_id: &A, from: &B, to: &B, balance: &C) -> Result {
+            for_tuples!( #( Tuple::before_asset_transfer(asset_id, from, to, balance)?; )* );
+            Ok(())
+        }
+    }
+    impl<A> BeforeAssetDestroy<A> for () {}
+    impl<A, B> OnAssetReaped<A, B> for () {}
 }
 use crate::traits::*;
 
+#[derive(Encode, Decode, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum ReleaseTrigger {
+    PhaseChange,
+    BlockNumber(u64),
+}
+impl Default for ReleaseTrigger {
+    fn default() -> Self {
+        ReleaseTrigger::PhaseChange
+    }
+}
+
+/// something that releases a locked balance term by term
+pub trait ReleasePack {
+    type Balance;
+    type AssetId;
+    type AccountId;
+
+    fn is_empty(&self) -> bool;
+    fn release(&mut self) -> Option<Self::Balance>;
+    fn get_asset_id(&self) -> Self::AssetId;
+    fn get_owner(&self) -> Self::AccountId;
+    fn check_release_trigger(&self, t: &ReleaseTrigger) -> bool;
+}
+
+/// a vesting schedule releases "per_term" every "period" blocks starting from block "start"
+/// a cliff schedule is a single term released at block "start"
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct VestingSchedule<Balance, AssetId, AccountId> {
+    pub asset_id: AssetId,
+    pub owner: AccountId,
+    pub start: u64,
+    pub period: u64,
+    pub terms_total: u32,
+    pub terms_left: u32,
+    pub per_term: Balance,
+    /// whatever can't be split evenly is released with the last term
+    pub remainder: Balance,
+}
+
+impl<Balance, AssetId, AccountId> VestingSchedule<Balance, AssetId, AccountId>
+where
+    Balance: SimpleArithmetic + Copy,
+{
+    pub fn new(
+        asset_id: AssetId,
+        owner: AccountId,
+        total: Balance,
+        start: u64,
+        period: u64,
+        terms: u32,
+    ) -> Self {
+        let per_term = total / Balance::from(terms);
+        VestingSchedule {
+            asset_id,
+            owner,
+            start,
+            period,
+            terms_total: terms,
+            terms_left: terms,
+            per_term,
+            remainder: total - per_term * Balance::from(terms),
+        }
+    }
+
+    pub fn get_total_balance(&self) -> Balance {
+        if self.terms_left == 0 {
+            Zero::zero()
+        } else {
+            self.per_term * Balance::from(self.terms_left) + self.remainder
+        }
+    }
+
+    /// the block number the next term is released at
+    fn next_release_at(&self) -> u64 {
+        let released = (self.terms_total - self.terms_left) as u64;
+        self.start.saturating_add(self.period.saturating_mul(released))
+    }
+
+    /// release every term due at block "now" in one go, returns the balance released
+    pub fn release_due(&mut self, now: u64) -> Balance {
+        if self.terms_left == 0 || now < self.start {
+            return Zero::zero();
+        }
+        // the first term is released at "start", a zero period only comes with a single term
+        let due = if self.period == 0 {
+            self.terms_total
+        } else {
+            cmp::min((now - self.start) / self.period + 1, self.terms_total as u64) as u32
+        };
+        let released = self.terms_total - self.terms_left;
+        if due <= released {
+            return Zero::zero();
+        }
+        self.terms_left -= due - released;
+        let amount = self.per_term * Balance::from(due - released);
+        if self.terms_left == 0 {
+            amount + self.remainder
+        } else {
+            amount
+        }
+    }
+}
+
+impl<Balance, AssetId, AccountId> ReleasePack for VestingSchedule<Balance, AssetId, AccountId>
+where
+    Balance: SimpleArithmetic + Copy,
+    AssetId: Copy,
+    AccountId: Clone,
+{
+    type Balance = Balance;
+    type AssetId = AssetId;
+    type AccountId = AccountId;
+
+    fn is_empty(&self) -> bool {
+        self.terms_left == 0
+    }
+    fn release(&mut self) -> Option<Balance> {
+        if self.terms_left == 0 {
+            return None;
+        }
+        self.terms_left -= 1;
+        if self.terms_left == 0 {
+            Some(self.per_term + self.remainder)
+        } else {
+            Some(self.per_term)
+        }
+    }
+    fn get_asset_id(&self) -> AssetId {
+        self.asset_id
+    }
+    fn get_owner(&self) -> AccountId {
+        self.owner.clone()
+    }
+    fn check_release_trigger(&self, t: &ReleaseTrigger) -> bool {
+        match t {
+            ReleaseTrigger::BlockNumber(now) => {
+                !self.is_empty() && *now >= self.next_release_at()
+            }
+            _ => false,
+        }
+    }
+}
+
+/// the named reservation that holds vesting balances
+pub const VESTING_ID: ReserveIdentifier = *b"vesting ";
+
+/// how many vesting schedules an account can have for a single asset
+pub const MAX_VESTING_SCHEDULES: usize = 16;
+
+/// the most terms a vesting schedule can have
+pub const MAX_VESTING_TERMS: u32 = 1000;
+
+/// identifies who a named reservation belongs to, e.g. a module plus a loan id
+pub type ReserveIdentifier = [u8; 8];
+
+/// how many named reservations an account can have for a single asset
+pub const MAX_NAMED_RESERVES: usize = 16;
+
+/// how many balance checkpoints are kept per account and asset, the oldest one goes first
+/// the balance before the oldest kept checkpoint is unknown from then on
+pub const MAX_CHECKPOINTS: usize = 100;
+
+/// the module account owns the assets created by T::AdminOrigin and holds their permissions
+pub const MODULE_ID: ModuleId = ModuleId(*b"dfx/asst");
+
+/// the layout version of the storage, assets of an older version are migrated by the runtime upgrade
+pub const STORAGE_VERSION: u32 = 1;
+
+/// how many accounts can be handled by a single "destroy_accounts"
+pub const MAX_DESTROY_BATCH: usize = 64;
+
+/// at most "limit" can be minted within "window" blocks
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct MintLimit<Balance, BlockNumber> {
+    pub window: BlockNumber,
+    pub limit: Balance,
+}
+
+/// what to do when a transfer would leave less than the minimum balance in the sender's account
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum DustPolicy {
+    /// send the remainder to the recipient as well
+    Sweep,
+    /// refuse the transfer
+    Deny,
+}
+impl Default for DustPolicy {
+    fn default() -> Self {
+        DustPolicy::Deny
+    }
+}
+
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct MinimumBalance<Balance> {
+    pub amount: Balance,
+    pub policy: DustPolicy,
+}
+
+pub const FEE_BPS_PREC: u32 = 10000;
+/// how many tiers a tiered transfer fee can have
+pub const MAX_FEE_TIERS: usize = 8;
+
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum FeeKind<Balance> {
+    Flat(Balance),
+    /// in basis points of the transferred amount
+    BasisPoints(u32),
+    /// (threshold, basis points), ascending by threshold
+    /// a transfer pays the rate of the highest threshold not above its amount, nothing below the first one
+    Tiered(Vec<(Balance, u32)>),
+}
+
+/// the fee a sender pays on top of the transferred amount, to "destination"
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct TransferFee<Balance, AccountId> {
+    pub kind: FeeKind<Balance>,
+    pub destination: AccountId,
+}
+
+impl<Balance, AccountId> TransferFee<Balance, AccountId>
+where
+    Balance: SimpleArithmetic + Copy,
+{
+    pub fn fee_of(&self, amount: Balance) -> Balance {
+        let bps = match &self.kind {
+            FeeKind::Flat(fee) => return *fee,
+            FeeKind::BasisPoints(bps) => *bps,
+            FeeKind::Tiered(tiers) => match tiers.iter().rev().find(|(t, _)| amount >= *t) {
+                Some((_, bps)) => *bps,
+                None => return Zero::zero(),
+            },
+        };
+        // divide first so it can't overflow
+        let prec = Balance::from(FEE_BPS_PREC);
+        let bps = Balance::from(bps);
+        amount / prec * bps + amount % prec * bps / prec
+    }
+
+    pub fn is_valid(&self) -> bool {
+        match &self.kind {
+            FeeKind::Flat(_) => true,
+            FeeKind::BasisPoints(bps) => *bps <= FEE_BPS_PREC,
+            FeeKind::Tiered(tiers) => {
+                !tiers.is_empty()
+                    && tiers.len() <= MAX_FEE_TIERS
+                    && tiers.iter().all(|(_, bps)| *bps <= FEE_BPS_PREC)
+                    && tiers.windows(2).all(|w| w[0].0 < w[1].0)
+            }
+        }
+    }
+}
+
+/// weight of a batch call, proportional to the number of items in the batch
+pub struct BatchWeight(Weight);
+
+impl<A, L> WeighData<(&A, &Vec<L>)> for BatchWeight {
+    fn weigh_data(&self, (_, legs): (&A, &Vec<L>)) -> Weight {
+        self.0.saturating_mul(legs.len() as Weight)
+    }
+}
+
+impl<A, L> ClassifyDispatch<(&A, &Vec<L>)> for BatchWeight {
+    fn classify_dispatch(&self, _: (&A, &Vec<L>)) -> DispatchClass {
+        DispatchClass::Normal
+    }
+}
+
+impl PaysFee for BatchWeight {
+    fn pays_fee(&self) -> bool {
+        true
+    }
+}
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+
 /// The module's configuration trait.
-pub trait Trait: generic_asset::Trait + sudo::Trait {
+pub trait Trait: generic_asset::Trait {
     type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
 
+    /// the origin that administrates assets, e.g. a collective, a multisig or sudo
+    type AdminOrigin: EnsureOrigin<Self::Origin>;
+
     type BeforeAssetTransfer: crate::traits::BeforeAssetTransfer<
         Self::AssetId,
         Self::AccountId,
@@ -116,6 +447,15 @@ pub trait Trait: generic_asset::Trait + sudo::Trait {
     type OnAssetCreate: crate::traits::OnAssetCreate<Self::AssetId>;
     type OnAssetMint: crate::traits::OnAssetMint<Self::AssetId, Self::AccountId, Self::Balance>;
     type OnAssetBurn: crate::traits::OnAssetBurn<Self::AssetId, Self::AccountId, Self::Balance>;
+    type OnAssetReaped: crate::traits::OnAssetReaped<Self::AssetId, Self::AccountId>;
+    type BeforeAssetDestroy: crate::traits::BeforeAssetDestroy<Self::AssetId>;
+
+    /// the native currency that creation deposits are reserved from
+    type Currency: ReservableCurrency<Self::AccountId>;
+    /// how much native currency is reserved from whoever creates an asset without the admin
+    type CreationDeposit: Get<BalanceOf<Self>>;
+    /// how many blocks an account has to wait before it can create another asset
+    type CreationCooldown: Get<Self::BlockNumber>;
 }
 
 // This module's storage items.
@@ -123,11 +463,60 @@ decl_storage! {
     trait Store for Module<T: Trait> as Assets {
         /// "Symbols" can only keep Vec<u8>, and utf8 safty is totally on the client side
         pub Symbols get(symbols) config() : map T::AssetId => Vec<u8>;
+        /// the layout version of the storage, the runtime upgrade migrates it to "STORAGE_VERSION"
+        StorageVersion get(storage_version) build(|_config: &GenesisConfig<T>| STORAGE_VERSION) : u32;
+        /// symbol => the asset using it, a symbol belongs to a single asset at a time
+        pub AssetIdBySymbol get(asset_id_of_symbol) : map Vec<u8> => Option<T::AssetId>;
+        /// the account that manages the asset through this module
+        /// the generic_asset permissions of every asset stay with the module account
+        pub Owners get(owner) : map T::AssetId => Option<T::AccountId>;
+        /// the deposit reserved for an asset created by a signed account, refunded when the asset is destroyed
+        pub CreationDeposits get(creation_deposit) : map T::AssetId => Option<(T::AccountId, BalanceOf<T>)>;
+        /// the block number of the last asset an account has created with a deposit
+        LastCreatedAt get(last_created_at) : map T::AccountId => Option<T::BlockNumber>;
+        /// accounts that are not allowed to move a specific asset out
+        pub FrozenAccounts get(is_frozen) : double_map T::AssetId, blake2_256(T::AccountId) => bool;
+        /// asset, (owner, spender) => how much the spender can still transfer from the owner
+        pub Allowances get(allowance) : double_map T::AssetId, blake2_256((T::AccountId, T::AccountId)) => T::Balance;
+        /// the maximum total issuance of an asset, no caps at all if None
+        pub SupplyCaps get(supply_cap) : map T::AssetId => Option<T::Balance>;
+        /// how much of an asset can be minted within a window of blocks, no limits at all if None
+        pub MintLimits get(mint_limit) : map T::AssetId => Option<MintLimit<T::Balance, T::BlockNumber>>;
+        /// (block number the current window starts at, balance minted within the current window)
+        MintWindows get(mint_window) : map T::AssetId => (T::BlockNumber, T::Balance);
+        /// an account holding an asset must keep at least this amount of it, or nothing at all
+        pub MinimumBalances get(minimum_balance) : map T::AssetId => Option<MinimumBalance<T::Balance>>;
+        /// user initiated transfers of an asset pay this fee on top of the amount
+        pub TransferFees get(transfer_fee) : map T::AssetId => Option<TransferFee<T::Balance, T::AccountId>>;
+        /// assets being destroyed can't be transferred or minted anymore
+        pub Destroying get(is_destroying) : map T::AssetId => bool;
+        /// assets destroyed for good, their ids can't be minted or created again
+        pub Destroyed get(is_destroyed) : map T::AssetId => bool;
+        /// whether the holders of an asset are indexed, it's opt-in per asset
+        pub HolderIndexEnabled get(holder_index_enabled) : map T::AssetId => bool;
+        /// number of accounts holding an asset, only tracked when the holder index is enabled
+        pub HolderCount get(holder_count) : map T::AssetId => u32;
+        /// (asset, position) => holder
+        Holders get(holder_at) : map (T::AssetId, u32) => T::AccountId;
+        /// (asset, holder) => position
+        HolderPositions get(holder_position) : map (T::AssetId, T::AccountId) => Option<u32>;
+        /// the block number since which balance checkpoints of an asset are recorded, it's opt-in per asset
+        pub CheckpointsEnabledAt get(checkpoints_enabled_at) : map T::AssetId => Option<T::BlockNumber>;
+        /// asset, account => [(block number, total balance at the end of that block)], ascending by block number
+        /// at most "MAX_CHECKPOINTS" of them
+        pub Checkpoints get(checkpoints) : double_map T::AssetId, blake2_256(T::AccountId) => Vec<(T::BlockNumber, T::Balance)>;
+        /// (reservation id, asset, account) => balance reserved under that id
+        /// the sum of all named reservations of an account never exceeds its reserved balance in generic_asset
+        pub NamedReserves get(named_reserve) : map (ReserveIdentifier, T::AssetId, T::AccountId) => T::Balance;
+        /// (asset, account) => the ids it has something reserved under, at most "MAX_NAMED_RESERVES"
+        pub ReserveIds get(reserve_ids) : map (T::AssetId, T::AccountId) => Vec<ReserveIdentifier>;
+        /// vesting balances of an account are reserved under "VESTING_ID" until released by these schedules
+        pub VestingSchedules get(vesting_schedules) : map (T::AssetId, T::AccountId) => Vec<VestingSchedule<T::Balance, T::AssetId, T::AccountId>>;
     }
 
     add_extra_genesis {
         build(|config: &GenesisConfig<T>| {
-            let origin = <sudo::Module<T>>::key();
+            let origin = <Module<T>>::account_id();
             let options = AssetOptions {
                 initial_issuance: T::Balance::from(0),
                 permissions: PermissionLatest {
@@ -138,6 +527,8 @@ decl_storage! {
             };
             for i in &config.symbols {
                 <generic_asset::Module<T>>::create_asset(Some(i.0), None, options.clone()).unwrap();
+                <AssetIdBySymbol<T>>::insert(&i.1, i.0);
+                <Owners<T>>::insert(i.0, origin.clone());
             }
         });
     }
@@ -151,12 +542,17 @@ decl_module! {
         // this is needed only if you are using events in your module
         pub fn deposit_event() = default;
 
-        /// create a new asset with full permissions granted to whoever make the call
-        /// *sudo or proposal approved only*
+        fn on_runtime_upgrade() {
+            Self::migrate_assets();
+        }
+
+        /// create a new asset with full permissions granted to the module account
+        /// *admin only*
         #[weight = SimpleDispatchInfo::FreeOperational]
         pub fn create(origin, initial_balance: T::Balance, symbol: Vec<u8>) -> Result {
-            ensure_root(origin)?;
-            let root_account_id = <sudo::Module<T>>::key();
+            T::AdminOrigin::ensure_origin(origin)?;
+            Self::ensure_symbol_free(&symbol)?;
+            let root_account_id = Self::account_id();
 
             let options = AssetOptions {
                 initial_issuance:initial_balance,
@@ -168,9 +564,57 @@ decl_module! {
             };
 
             let asset_id = <generic_asset::Module<T>>::next_asset_id();
-            <generic_asset::Module<T>>::create_asset(None, Some(root_account_id), options)?;
-            <Symbols<T>>::insert(asset_id, symbol.clone());
+            <generic_asset::Module<T>>::create_asset(None, Some(root_account_id.clone()), options)?;
+            Self::insert_symbol(asset_id, symbol);
+            <Owners<T>>::insert(asset_id, root_account_id);
+
+            Ok(())
+        }
+
+        /// create a new asset without the admin, a "CreationDeposit" of native currency is reserved from the caller
+        /// the caller becomes the owner of the asset, and can only create another one after "CreationCooldown" blocks
+        /// the permissions are granted to the module account, the owner mints through "mint" like the admin
+        #[weight = SimpleDispatchInfo::FixedNormal(1_000_000)]
+        pub fn create_with_deposit(origin, initial_balance: T::Balance, symbol: Vec<u8>) -> Result {
+            let who = ensure_signed(origin)?;
+            ensure!(!symbol.is_empty(), "symbol can't be empty");
+            Self::ensure_symbol_free(&symbol)?;
+
+            let now = <system::Module<T>>::block_number();
+            if let Some(last) = Self::last_created_at(&who) {
+                ensure!(now >= last.saturating_add(T::CreationCooldown::get()), "asset creation is rate limited");
+            }
+
+            let asset_id = <generic_asset::Module<T>>::next_asset_id();
+            T::BeforeAssetCreate::before_asset_create(&asset_id)?;
+
+            let deposit = T::CreationDeposit::get();
+            T::Currency::reserve(&who, deposit)?;
+
+            let account = Self::account_id();
+            let options = AssetOptions {
+                initial_issuance: initial_balance,
+                permissions: PermissionLatest {
+                    update: generic_asset::Owner::Address(account.clone()),
+                    mint: generic_asset::Owner::Address(account.clone()),
+                    burn: generic_asset::Owner::Address(account),
+                },
+            };
+            <generic_asset::Module<T>>::create_asset(None, Some(who.clone()), options)
+                .or_else(|err| -> Result {
+                    T::Currency::unreserve(&who, deposit);
+                    Err(err)
+                })?;
+
+            Self::insert_symbol(asset_id, symbol);
+            <Owners<T>>::insert(asset_id, who.clone());
+            <CreationDeposits<T>>::insert(asset_id, (who.clone(), deposit));
+            <LastCreatedAt<T>>::insert(&who, now);
+            Self::balance_changed(&asset_id, &who);
+            // ignore the err
+            T::OnAssetCreate::on_asset_create(&asset_id).unwrap_or_default();
 
+            Self::deposit_event(RawEvent::Created(asset_id, who, deposit));
             Ok(())
         }
 
@@ -179,63 +623,435 @@ decl_module! {
         #[weight = SimpleDispatchInfo::FixedNormal(0)]
         pub fn transfer(origin, #[compact] asset_id: T::AssetId, to: T::AccountId, #[compact] amount: T::Balance) -> Result {
             let o = ensure_signed(origin)?;
-            T::BeforeAssetTransfer::before_asset_transfer(&asset_id, &o, &to, &amount)?;
-            <generic_asset::Module<T>>::make_transfer_with_event(&asset_id, &o, &to, amount)?;
-            // ignore the err
-            T::OnAssetTransfer::on_asset_transfer(&asset_id, &o, &to, &amount).unwrap_or_default();
+            Self::transfer_with_hooks(&asset_id, &o, &to, amount)?;
             Ok(())
             // generic_asset::Call::<T>::transfer(asset_id, to, amount).dispatch(origin)
         }
 
+        /// transfer the asset from the caller to many accounts at once, either all legs succeed or none of them
+        /// each leg goes through T::BeforeAssetTransfer & T::OnAssetTransfer as a single "transfer" does
+        #[weight = BatchWeight(10_000)]
+        pub fn batch_transfer(origin, #[compact] asset_id: T::AssetId, legs: Vec<(T::AccountId, T::Balance)>) -> Result {
+            let o = ensure_signed(origin)?;
+            Self::make_batch_transfer(&asset_id, &o, &legs)
+        }
+
+        /// allow "spender" to transfer at most "amount" of the caller's asset, overrides the previous allowance
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn approve(origin, #[compact] asset_id: T::AssetId, spender: T::AccountId, #[compact] amount: T::Balance) -> Result {
+            let owner = ensure_signed(origin)?;
+            ensure!(owner != spender, "can't approve to self");
+            if amount.is_zero() {
+                <Allowances<T>>::remove(&asset_id, &(owner.clone(), spender.clone()));
+            } else {
+                <Allowances<T>>::insert(&asset_id, &(owner.clone(), spender.clone()), amount);
+            }
+            Self::deposit_event(RawEvent::Approved(asset_id, owner, spender, amount));
+            Ok(())
+        }
+
+        /// transfer asset of "owner" on behalf of him, the caller must have been approved enough allowance
+        /// the same hooks as "transfer" apply to the transfer from "owner" to "to"
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn transfer_from(origin, #[compact] asset_id: T::AssetId, owner: T::AccountId, to: T::AccountId, #[compact] amount: T::Balance) -> Result {
+            let spender = ensure_signed(origin)?;
+            // the transfer fee and the dust swept along are charged to the allowance too
+            let (fee, _) = Self::transfer_fee_of(&asset_id, &owner, amount);
+            let moved = Self::apply_dust_policy(&asset_id, &owner, &to, amount, fee)?;
+            let charged = moved.checked_add(&fee).ok_or("amount overflows")?;
+            ensure!(Self::allowance(&asset_id, &(owner.clone(), spender.clone())) >= charged, "allowance is not enough");
+            let moved = Self::transfer_with_hooks(&asset_id, &owner, &to, amount)?;
+            Self::spend_allowance(&asset_id, &owner, &spender, moved + fee)
+        }
+
+        /// stop an account from moving the asset out
+        /// *asset owner or admin only*
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn freeze(origin, #[compact] asset_id: T::AssetId, who: T::AccountId) -> Result {
+            Self::ensure_owner_or_admin(origin, &asset_id)?;
+            ensure!(!Self::is_frozen(&asset_id, &who), "account is already frozen");
+            <FrozenAccounts<T>>::insert(&asset_id, &who, true);
+            Self::deposit_event(RawEvent::Frozen(asset_id, who));
+            Ok(())
+        }
+
+        /// let a frozen account move the asset again
+        /// *asset owner or admin only*
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn thaw(origin, #[compact] asset_id: T::AssetId, who: T::AccountId) -> Result {
+            Self::ensure_owner_or_admin(origin, &asset_id)?;
+            ensure!(Self::is_frozen(&asset_id, &who), "account is not frozen");
+            <FrozenAccounts<T>>::remove(&asset_id, &who);
+            Self::deposit_event(RawEvent::Thawed(asset_id, who));
+            Ok(())
+        }
+
         // generic_asset<T>::update_permission delegation
         // pub fn update_permission(origin, #[compact] asset_id: T::AssetId, new_permission: PermissionLatest<T::AccountId>) -> Result {
         //     generic_asset::Call::<T>::update_permission(asset_id, new_permission).dispatch(origin)
         // }
 
         /// generic_asset<T>::mint delegation
-        #[weight = SimpleDispatchInfo::FreeOperational]
+        /// *asset owner or admin only*
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
         pub fn mint(origin, #[compact] asset_id: T::AssetId, to: T::AccountId, amount: T::Balance) -> Result {
-            ensure_root(origin)?;
-            let root_account_id = <sudo::Module<T>>::key();
-            T::BeforeAssetMint::before_asset_mint(&asset_id, &to, &amount)?;
-            generic_asset::Call::<T>::mint(asset_id.clone(), to.clone(), amount).dispatch(system::RawOrigin::Signed(root_account_id).into())?;
-            // ignore the err
-            T::OnAssetMint::on_asset_mint(&asset_id, &to, &amount).unwrap_or_default();
+            Self::ensure_owner_or_admin(origin, &asset_id)?;
+            Self::make_mint(&asset_id, &to, amount)
+        }
+
+        /// set the maximum total issuance of an asset, zero means no caps
+        /// *asset owner or admin only*
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn set_supply_cap(origin, #[compact] asset_id: T::AssetId, cap: T::Balance) -> Result {
+            Self::ensure_owner_or_admin(origin, &asset_id)?;
+            if cap.is_zero() {
+                <SupplyCaps<T>>::remove(&asset_id);
+            } else {
+                <SupplyCaps<T>>::insert(&asset_id, cap);
+            }
+            Ok(())
+        }
+
+        /// set how much of an asset can be minted within "window" blocks, a zero window means no limits
+        /// *asset owner or admin only*
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn set_mint_limit(origin, #[compact] asset_id: T::AssetId, window: T::BlockNumber, limit: T::Balance) -> Result {
+            Self::ensure_owner_or_admin(origin, &asset_id)?;
+            if window.is_zero() {
+                <MintLimits<T>>::remove(&asset_id);
+            } else {
+                <MintLimits<T>>::insert(&asset_id, MintLimit { window, limit });
+            }
+            <MintWindows<T>>::remove(&asset_id);
+            Ok(())
+        }
+
+        /// set the minimum balance of an asset and what to do with the dust below it, zero means no minimum
+        /// *asset owner or admin only*
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn set_minimum_balance(origin, #[compact] asset_id: T::AssetId, amount: T::Balance, policy: DustPolicy) -> Result {
+            Self::ensure_owner_or_admin(origin, &asset_id)?;
+            if amount.is_zero() {
+                <MinimumBalances<T>>::remove(&asset_id);
+            } else {
+                <MinimumBalances<T>>::insert(&asset_id, MinimumBalance { amount, policy });
+            }
+            Ok(())
+        }
+
+        /// set the fee policy of user initiated transfers of an asset, "None" means no fee
+        /// *asset owner or admin only*
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn set_transfer_fee(origin, #[compact] asset_id: T::AssetId, fee: Option<TransferFee<T::Balance, T::AccountId>>) -> Result {
+            Self::ensure_owner_or_admin(origin, &asset_id)?;
+            match fee {
+                Some(fee) => {
+                    ensure!(fee.is_valid(), "invalid transfer fee");
+                    <TransferFees<T>>::insert(&asset_id, fee);
+                }
+                None => <TransferFees<T>>::remove(&asset_id),
+            }
+            Ok(())
+        }
+
+        /// start indexing the holders of an asset
+        /// accounts holding the asset before the index is enabled should be added by "index_holders"
+        /// *asset owner or admin only*
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn enable_holder_index(origin, #[compact] asset_id: T::AssetId) -> Result {
+            Self::ensure_owner_or_admin(origin, &asset_id)?;
+            ensure!(Self::asset_exists(&asset_id), "asset doesn't exist");
+            <HolderIndexEnabled<T>>::insert(&asset_id, true);
+            Ok(())
+        }
+
+        /// add existing holders of an asset to its index, accounts not holding anything are ignored
+        #[weight = BatchWeight(10_000)]
+        pub fn index_holders(origin, #[compact] asset_id: T::AssetId, accounts: Vec<T::AccountId>) -> Result {
+            ensure_signed(origin)?;
+            ensure!(Self::holder_index_enabled(&asset_id), "holder index is not enabled");
+            for who in &accounts {
+                Self::update_holder(&asset_id, who);
+            }
+            Ok(())
+        }
+
+        /// start recording balance checkpoints of an asset
+        /// the first change of an account records its balance before the change at the block checkpoints were enabled
+        /// *asset owner or admin only*
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn enable_checkpoints(origin, #[compact] asset_id: T::AssetId) -> Result {
+            Self::ensure_owner_or_admin(origin, &asset_id)?;
+            ensure!(Self::asset_exists(&asset_id), "asset doesn't exist");
+            ensure!(Self::checkpoints_enabled_at(&asset_id).is_none(), "checkpoints are already enabled");
+            <CheckpointsEnabledAt<T>>::insert(&asset_id, <system::Module<T>>::block_number());
+            Ok(())
+        }
+
+        /// record the current balances of the given accounts as checkpoints
+        #[weight = BatchWeight(10_000)]
+        pub fn checkpoint_accounts(origin, #[compact] asset_id: T::AssetId, accounts: Vec<T::AccountId>) -> Result {
+            ensure_signed(origin)?;
+            ensure!(Self::checkpoints_enabled_at(&asset_id).is_some(), "checkpoints are not enabled");
+            for who in &accounts {
+                Self::seed_checkpoint(&asset_id, who);
+                Self::write_checkpoint(&asset_id, who);
+            }
+            Ok(())
+        }
+
+        /// lock "total" of the asset in the account of "who", releasing it in "terms" terms every "period" blocks from block "start"
+        /// a single term makes a cliff schedule
+        /// an asset owner funds the schedule from its own balance, the admin mints it within the limits of the asset
+        /// *asset owner or admin only*
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn vest(origin, #[compact] asset_id: T::AssetId, who: T::AccountId, total: T::Balance, start: T::BlockNumber, period: T::BlockNumber, terms: u32) -> Result {
+            Self::ensure_owner_or_admin(origin.clone(), &asset_id)?;
+            ensure!(!total.is_zero() && terms > 0, "zero is not allowed");
+            ensure!(terms == 1 || !period.is_zero(), "period can't be zero");
+            ensure!(terms <= MAX_VESTING_TERMS, "too many terms");
+            ensure!(total >= T::Balance::from(terms), "total is less than a unit per term");
+            let key = (asset_id, who.clone());
+            ensure!(Self::vesting_schedules(&key).len() < MAX_VESTING_SCHEDULES, "too many vesting schedules");
+
+            // None for the admin
+            let funder = T::AdminOrigin::try_origin(origin)
+                .err()
+                .and_then(|o| ensure_signed(o).ok());
+            match &funder {
+                Some(from) if *from != who => Self::make_transfer_with_event(&asset_id, from, &who, total)?,
+                Some(_) => {}
+                None => Self::make_mint(&asset_id, &who, total)?,
+            }
+            Self::reserve_named(&VESTING_ID, &asset_id, &who, total).or_else(|err| -> Result {
+                match &funder {
+                    Some(from) if *from != who => Self::make_transfer(&asset_id, &who, from, total)?,
+                    Some(_) => {}
+                    None => Self::make_burn(&asset_id, &who, total)?,
+                }
+                Err(err)
+            })?;
+            let schedule = VestingSchedule::new(
+                asset_id,
+                who.clone(),
+                total,
+                start.saturated_into::<u64>(),
+                period.saturated_into::<u64>(),
+                terms,
+            );
+            <VestingSchedules<T>>::mutate(&key, |v| v.push(schedule));
+            Self::deposit_event(RawEvent::Vested(asset_id, who, total));
+            Ok(())
+        }
+
+        /// release whatever is due in the caller's vesting schedules of the asset
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn claim_vested(origin, #[compact] asset_id: T::AssetId) -> Result {
+            let who = ensure_signed(origin)?;
+            let key = (asset_id, who.clone());
+            ensure!(<VestingSchedules<T>>::exists(&key), "no vesting schedules");
+
+            let now = <system::Module<T>>::block_number().saturated_into::<u64>();
+            let mut released = T::Balance::zero();
+            let schedules: Vec<_> = Self::vesting_schedules(&key)
+                .into_iter()
+                .map(|mut s| {
+                    released += s.release_due(now);
+                    s
+                })
+                .filter(|s| !s.is_empty())
+                .collect();
+            ensure!(!released.is_zero(), "nothing to claim");
+
+            if schedules.is_empty() {
+                <VestingSchedules<T>>::remove(&key);
+            } else {
+                <VestingSchedules<T>>::insert(&key, schedules);
+            }
+            let claimed = Self::unreserve_named(&VESTING_ID, &asset_id, &who, released);
+            Self::deposit_event(RawEvent::VestingClaimed(asset_id, who, claimed));
+            Ok(())
+        }
+
+        /// start destroying an asset, from now on it can't be transferred or minted
+        /// *asset owner or admin only*
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn start_destroy(origin, #[compact] asset_id: T::AssetId) -> Result {
+            Self::ensure_owner_or_admin(origin, &asset_id)?;
+            ensure!(Self::asset_exists(&asset_id), "asset doesn't exist");
+            ensure!(!Self::is_destroying(&asset_id), "asset is being destroyed");
+            T::BeforeAssetDestroy::before_asset_destroy(&asset_id)?;
+            <Destroying<T>>::insert(&asset_id, true);
+            Self::deposit_event(RawEvent::DestroyStarted(asset_id));
+            Ok(())
+        }
+
+        /// burn all the balances of a destroying asset held by the given accounts, at most "MAX_DESTROY_BATCH" accounts each call
+        /// *asset owner or admin only*
+        #[weight = BatchWeight(10_000)]
+        pub fn destroy_accounts(origin, #[compact] asset_id: T::AssetId, accounts: Vec<T::AccountId>) -> Result {
+            Self::ensure_owner_or_admin(origin, &asset_id)?;
+            ensure!(Self::is_destroying(&asset_id), "asset is not being destroyed");
+            ensure!(accounts.len() <= MAX_DESTROY_BATCH, "too many accounts in batch");
+            for who in &accounts {
+                Self::burn_all(&asset_id, who)?;
+            }
+            Ok(())
+        }
+
+        /// burn all of the caller's balance of a destroying asset, instead of waiting for "destroy_accounts"
+        /// T::OnAssetBurn lets the module behind the asset pay the holder out
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn redeem_destroying(origin, #[compact] asset_id: T::AssetId) -> Result {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::is_destroying(&asset_id), "asset is not being destroyed");
+            let amount = <generic_asset::Module<T>>::total_balance(&asset_id, &who);
+            ensure!(!amount.is_zero(), "nothing to redeem");
+            Self::burn_all(&asset_id, &who)?;
+            Self::deposit_event(RawEvent::Redeemed(asset_id, who, amount));
+            Ok(())
+        }
+
+        /// remove a destroying asset for good once nothing of it is left
+        /// *asset owner or admin only*
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn finish_destroy(origin, #[compact] asset_id: T::AssetId) -> Result {
+            Self::ensure_owner_or_admin(origin, &asset_id)?;
+            ensure!(Self::is_destroying(&asset_id), "asset is not being destroyed");
+            ensure!(
+                <generic_asset::Module<T>>::total_issuance(&asset_id).is_zero(),
+                "asset still has balances"
+            );
+
+            <AssetIdBySymbol<T>>::remove(Self::symbols(&asset_id));
+            <Symbols<T>>::remove(&asset_id);
+            <SupplyCaps<T>>::remove(&asset_id);
+            <MintLimits<T>>::remove(&asset_id);
+            <MintWindows<T>>::remove(&asset_id);
+            <MinimumBalances<T>>::remove(&asset_id);
+            <TransferFees<T>>::remove(&asset_id);
+            <CheckpointsEnabledAt<T>>::remove(&asset_id);
+            <Checkpoints<T>>::remove_prefix(&asset_id);
+            <FrozenAccounts<T>>::remove_prefix(&asset_id);
+            <Allowances<T>>::remove_prefix(&asset_id);
+            <Destroying<T>>::remove(&asset_id);
+            <HolderIndexEnabled<T>>::remove(&asset_id);
+            <HolderCount<T>>::remove(&asset_id);
+            Self::refund_creation_deposit(&asset_id);
+            <Owners<T>>::remove(&asset_id);
+            <Destroyed<T>>::insert(&asset_id, true);
+
+            Self::deposit_event(RawEvent::Destroyed(asset_id));
             Ok(())
         }
 
         /// generic_asset<T>::burn delegation
+        /// *admin only*
         #[weight = SimpleDispatchInfo::FreeOperational]
         pub fn burn(origin, #[compact] asset_id: T::AssetId, to: T::AccountId, amount:T::Balance) -> Result {
-            ensure_root(origin)?;
-            let root_account_id = <sudo::Module<T>>::key();
-            T::BeforeAssetBurn::before_asset_burn(&asset_id, &to, &amount)?;
-            generic_asset::Call::<T>::burn(asset_id.clone(), to.clone(), amount).dispatch(system::RawOrigin::Signed(root_account_id).into())?;
-            // ignore the err
-            T::OnAssetBurn::on_asset_burn(&asset_id, &to, &amount).unwrap_or_default();
-            Ok(())
+            T::AdminOrigin::ensure_origin(origin)?;
+            Self::make_burn(&asset_id, &to, amount)
         }
 
         /// generic_asset<T>::create_reserved delegation
+        /// the module account owns the asset and has to hold its burn permission, so the asset can be destroyed
+        /// *admin only*
         #[weight = SimpleDispatchInfo::FreeOperational]
-        pub fn create_reserved(origin, asset_id: T::AssetId, options: AssetOptions<T::Balance, T::AccountId>) -> Result {
-            ensure_root(origin)?;
-            let root_account_id = <sudo::Module<T>>::key();
-            generic_asset::Call::<T>::create_reserved(asset_id, options).dispatch(system::RawOrigin::Signed(root_account_id).into())
+        pub fn create_reserved(origin, asset_id: T::AssetId, options: AssetOptions<T::Balance, T::AccountId>, symbol: Vec<u8>) -> Result {
+            T::AdminOrigin::ensure_origin(origin)?;
+            ensure!(!Self::is_destroyed(&asset_id), "asset is destroyed");
+            ensure!(!symbol.is_empty(), "symbol can't be empty");
+            Self::ensure_symbol_free(&symbol)?;
+            let account = Self::account_id();
+            ensure!(
+                options.permissions.burn == generic_asset::Owner::Address(account.clone()),
+                "burn permission must stay with the module account"
+            );
+            generic_asset::Call::<T>::create_reserved(asset_id, options).dispatch(system::RawOrigin::Signed(account.clone()).into())?;
+            Self::insert_symbol(asset_id, symbol);
+            <Owners<T>>::insert(asset_id, account);
+            Ok(())
         }
     }
 }
 
 impl<T: Trait> Module<T> {
-    pub fn ensure_signed_root(
-        origin: <T as system::Trait>::Origin,
-    ) -> result::Result<<T as system::Trait>::AccountId, &'static str> {
-        let who = ensure_signed(origin)?;
-        ensure!(
-            who == <sudo::Module<T>>::key(),
-            Error::RequireRootOrigin.into()
-        );
-        Ok(who)
+    /// the module account, which owns the assets created by T::AdminOrigin
+    pub fn account_id() -> T::AccountId {
+        MODULE_ID.into_account()
+    }
+
+    /// mint as the module account, for other modules to mint without going through T::AdminOrigin
+    /// the same hooks and limits as "mint" apply
+    pub fn make_mint(asset_id: &T::AssetId, to: &T::AccountId, amount: T::Balance) -> Result {
+        ensure!(!Self::is_destroyed(asset_id), "asset is destroyed");
+        ensure!(!Self::is_destroying(asset_id), "asset is being destroyed");
+        T::BeforeAssetMint::before_asset_mint(asset_id, to, &amount)?;
+        Self::ensure_can_mint(asset_id, amount)?;
+        Self::seed_checkpoint(asset_id, to);
+        generic_asset::Call::<T>::mint(asset_id.clone(), to.clone(), amount)
+            .dispatch(system::RawOrigin::Signed(Self::account_id()).into())?;
+        Self::note_minted(asset_id, amount);
+        // ignore the err
+        T::OnAssetMint::on_asset_mint(asset_id, to, &amount).unwrap_or_default();
+        Self::balance_changed(asset_id, to);
+        Ok(())
+    }
+
+    /// burn as the module account, for other modules to burn without going through T::AdminOrigin
+    pub fn make_burn(asset_id: &T::AssetId, to: &T::AccountId, amount: T::Balance) -> Result {
+        T::BeforeAssetBurn::before_asset_burn(asset_id, to, &amount)?;
+        Self::seed_checkpoint(asset_id, to);
+        generic_asset::Call::<T>::burn(asset_id.clone(), to.clone(), amount)
+            .dispatch(system::RawOrigin::Signed(Self::account_id()).into())?;
+        // ignore the err
+        T::OnAssetBurn::on_asset_burn(asset_id, to, &amount).unwrap_or_default();
+        Self::balance_changed(asset_id, to);
+        Ok(())
+    }
+
+    /// how much of an asset can still be minted right now, unlimited if None
+    pub fn mint_headroom(asset_id: &T::AssetId) -> Option<T::Balance> {
+        let by_cap = Self::supply_cap(asset_id).map(|cap| {
+            cap.checked_sub(&<generic_asset::Module<T>>::total_issuance(asset_id))
+                .unwrap_or_else(Zero::zero)
+        });
+        let by_limit = Self::mint_limit(asset_id).map(|l| {
+            let (_, minted) = Self::current_mint_window(asset_id, &l);
+            l.limit.checked_sub(&minted).unwrap_or_else(Zero::zero)
+        });
+        match (by_cap, by_limit) {
+            (Some(c), Some(l)) => Some(cmp::min(c, l)),
+            (c, l) => c.or(l),
+        }
+    }
+
+    fn ensure_can_mint(asset_id: &T::AssetId, amount: T::Balance) -> Result {
+        if let Some(headroom) = Self::mint_headroom(asset_id) {
+            ensure!(amount <= headroom, "exceed mint limits");
+        }
+        Ok(())
+    }
+
+    fn note_minted(asset_id: &T::AssetId, amount: T::Balance) {
+        if let Some(l) = Self::mint_limit(asset_id) {
+            let (start, minted) = Self::current_mint_window(asset_id, &l);
+            <MintWindows<T>>::insert(asset_id, (start, minted.saturating_add(amount)));
+        }
+    }
+
+    /// a window starts over once it has lasted for "window" blocks
+    fn current_mint_window(
+        asset_id: &T::AssetId,
+        l: &MintLimit<T::Balance, T::BlockNumber>,
+    ) -> (T::BlockNumber, T::Balance) {
+        let now = <system::Module<T>>::block_number();
+        let (start, minted) = Self::mint_window(asset_id);
+        if !<MintWindows<T>>::exists(asset_id) || now >= start.saturating_add(l.window) {
+            (now, Zero::zero())
+        } else {
+            (start, minted)
+        }
     }
 
     pub fn get_current_asset_id() -> T::AssetId {
@@ -246,26 +1062,569 @@ impl<T: Trait> Module<T> {
         <Symbols<T>>::exists(asset_id)
     }
 
+    pub fn total_issuance(asset_id: &T::AssetId) -> T::Balance {
+        <generic_asset::Module<T>>::total_issuance(asset_id)
+    }
+
+    /// the balance of "who" still locked by vesting schedules
+    pub fn vesting_balance(asset_id: &T::AssetId, who: &T::AccountId) -> T::Balance {
+        Self::vesting_schedules((*asset_id, who.clone()))
+            .iter()
+            .fold(Zero::zero(), |acc, s| acc + s.get_total_balance())
+    }
+
     pub fn free_balance(asset_id: &T::AssetId, who: &T::AccountId) -> T::Balance {
         <generic_asset::Module<T>>::free_balance(asset_id, who)
     }
 
+    fn ensure_symbol_free(symbol: &[u8]) -> Result {
+        ensure!(!<AssetIdBySymbol<T>>::exists(symbol.to_vec()), "symbol is already taken");
+        Ok(())
+    }
+
+    fn insert_symbol(asset_id: T::AssetId, symbol: Vec<u8>) {
+        <AssetIdBySymbol<T>>::insert(&symbol, asset_id);
+        <Symbols<T>>::insert(asset_id, symbol);
+    }
+
+    /// assets created before owners were introduced are administrated by the old sudo key
+    /// hand them over to the module account like the assets T::AdminOrigin creates, and index their symbols
+    fn migrate_assets() {
+        if Self::storage_version() >= STORAGE_VERSION {
+            return;
+        }
+        let account = Self::account_id();
+        let permissions: generic_asset::PermissionVersions<T::AccountId> = PermissionLatest {
+            update: generic_asset::Owner::Address(account.clone()),
+            mint: generic_asset::Owner::Address(account.clone()),
+            burn: generic_asset::Owner::Address(account.clone()),
+        }
+        .into();
+        let next = <generic_asset::Module<T>>::next_asset_id();
+        let mut asset_id = T::AssetId::zero();
+        while asset_id < next {
+            if Self::asset_exists(&asset_id) && Self::owner(&asset_id).is_none() {
+                <generic_asset::Permissions<T>>::insert(&asset_id, &permissions);
+                <Owners<T>>::insert(&asset_id, account.clone());
+                <AssetIdBySymbol<T>>::insert(Self::symbols(&asset_id), asset_id);
+            }
+            asset_id += One::one();
+        }
+        StorageVersion::put(STORAGE_VERSION);
+    }
+
+    pub fn is_owner(asset_id: &T::AssetId, who: &T::AccountId) -> bool {
+        Self::owner(asset_id).map_or(false, |o| o == *who)
+    }
+
+    pub fn ensure_owner_or_admin(origin: <T as system::Trait>::Origin, asset_id: &T::AssetId) -> Result {
+        let origin = match T::AdminOrigin::try_origin(origin) {
+            Ok(_) => return Ok(()),
+            Err(origin) => origin,
+        };
+        let who = ensure_signed(origin)?;
+        ensure!(Self::is_owner(asset_id, &who), "asset owner only");
+        Ok(())
+    }
+
+    pub fn ensure_not_frozen(asset_id: &T::AssetId, who: &T::AccountId) -> Result {
+        ensure!(!Self::is_destroying(asset_id), "asset is being destroyed");
+        ensure!(
+            !Self::is_frozen(asset_id, who),
+            "account is frozen for this asset"
+        );
+        Ok(())
+    }
+
+    /// give the creation deposit of an asset back to its creator
+    /// supposed to be called when the asset is destroyed
+    pub fn refund_creation_deposit(asset_id: &T::AssetId) {
+        if let Some((who, deposit)) = <CreationDeposits<T>>::take(asset_id) {
+            if !deposit.is_zero() {
+                T::Currency::unreserve(&who, deposit);
+            }
+            Self::deposit_event(RawEvent::DepositRefunded(*asset_id, who, deposit));
+        }
+    }
+
+    /// move "amount" from the free balance of "who" to its reserved balance under "id"
+    pub fn reserve_named(
+        id: &ReserveIdentifier,
+        asset_id: &T::AssetId,
+        who: &T::AccountId,
+        amount: T::Balance,
+    ) -> Result {
+        Self::ensure_not_frozen(asset_id, who)?;
+        let mut ids = Self::reserve_ids((*asset_id, who.clone()));
+        if !ids.contains(id) {
+            ensure!(ids.len() < MAX_NAMED_RESERVES, "too many named reserves");
+            ids.push(*id);
+        }
+        <generic_asset::Module<T>>::reserve(asset_id, who, amount)?;
+        <NamedReserves<T>>::mutate((*id, *asset_id, who.clone()), |v| *v += amount);
+        <ReserveIds<T>>::insert((*asset_id, who.clone()), ids);
+        Self::deposit_event(RawEvent::ReservedNamed(*id, *asset_id, who.clone(), amount));
+        Ok(())
+    }
+
+    /// move at most "amount" reserved under "id" back to the free balance of "who"
+    /// returns what is actually unreserved
+    pub fn unreserve_named(
+        id: &ReserveIdentifier,
+        asset_id: &T::AssetId,
+        who: &T::AccountId,
+        amount: T::Balance,
+    ) -> T::Balance {
+        let key = (*id, *asset_id, who.clone());
+        let named = Self::named_reserve(&key);
+        let amount = cmp::min(named, amount);
+        if amount.is_zero() {
+            return amount;
+        }
+        let left = <generic_asset::Module<T>>::unreserve(asset_id, who, amount);
+        let actual = amount - left;
+        Self::sub_named_reserve(key, actual);
+        Self::deposit_event(RawEvent::UnreservedNamed(*id, *asset_id, who.clone(), actual));
+        actual
+    }
+
+    /// move at most "amount" reserved under "id" from "who" to the free balance of "beneficiary"
+    /// returns what is actually moved
+    pub fn repatriate_reserved_named(
+        id: &ReserveIdentifier,
+        asset_id: &T::AssetId,
+        who: &T::AccountId,
+        beneficiary: &T::AccountId,
+        amount: T::Balance,
+    ) -> T::Balance {
+        let key = (*id, *asset_id, who.clone());
+        let named = Self::named_reserve(&key);
+        let amount = cmp::min(named, amount);
+        if amount.is_zero() {
+            return amount;
+        }
+        Self::seed_checkpoint(asset_id, who);
+        Self::seed_checkpoint(asset_id, beneficiary);
+        let left =
+            <generic_asset::Module<T>>::repatriate_reserved(asset_id, who, beneficiary, amount);
+        let actual = amount - left;
+        Self::sub_named_reserve(key, actual);
+        Self::balance_changed(asset_id, who);
+        Self::balance_changed(asset_id, beneficiary);
+        Self::deposit_event(RawEvent::RepatriatedNamed(
+            *id,
+            *asset_id,
+            who.clone(),
+            beneficiary.clone(),
+            actual,
+        ));
+        actual
+    }
+
+    fn sub_named_reserve(key: (ReserveIdentifier, T::AssetId, T::AccountId), amount: T::Balance) {
+        let left = Self::named_reserve(&key).saturating_sub(amount);
+        if left.is_zero() {
+            let (id, asset_id, who) = key.clone();
+            let mut ids = Self::reserve_ids((asset_id, who.clone()));
+            ids.retain(|i| *i != id);
+            if ids.is_empty() {
+                <ReserveIds<T>>::remove((asset_id, who));
+            } else {
+                <ReserveIds<T>>::insert((asset_id, who), ids);
+            }
+            <NamedReserves<T>>::remove(&key);
+        } else {
+            <NamedReserves<T>>::insert(&key, left);
+        }
+    }
+
+    /// the transfer path of a user initiated transfer
+    /// wrap 2 hooks around "make_transfer_with_event": T::BeforeAssetTransfer & T::OnAssetTransfer
+    /// the transfer fee of the asset is paid by "from" on top of the amount
+    /// returns the amount moved, more than "amount" if the asset's "DustPolicy" swept the dust along
+    pub fn transfer_with_hooks(
+        asset_id: &T::AssetId,
+        from: &T::AccountId,
+        to: &T::AccountId,
+        amount: T::Balance,
+    ) -> result::Result<T::Balance, &'static str> {
+        Self::ensure_not_frozen(asset_id, from)?;
+        let (fee, destination) = Self::transfer_fee_of(asset_id, from, amount);
+        let amount = Self::apply_dust_policy(asset_id, from, to, amount, fee)?;
+        T::BeforeAssetTransfer::before_asset_transfer(asset_id, from, to, &amount)?;
+        if let Some(destination) = &destination {
+            T::BeforeAssetTransfer::before_asset_transfer(asset_id, from, destination, &fee)?;
+        }
+        Self::seed_checkpoint(asset_id, from);
+        Self::seed_checkpoint(asset_id, to);
+        <generic_asset::Module<T>>::make_transfer_with_event(asset_id, from, to, amount)?;
+        if let Some(destination) = &destination {
+            Self::charge_transfer_fee(asset_id, from, destination, fee).or_else(|err| -> Result {
+                <generic_asset::Module<T>>::make_transfer_with_event(asset_id, to, from, amount)?;
+                Err(err)
+            })?;
+        }
+        // ignore the err
+        T::OnAssetTransfer::on_asset_transfer(asset_id, from, to, &amount).unwrap_or_default();
+        if let Some(destination) = &destination {
+            // ignore the err
+            T::OnAssetTransfer::on_asset_transfer(asset_id, from, destination, &fee).unwrap_or_default();
+        }
+        Self::balance_changed(asset_id, from);
+        Self::balance_changed(asset_id, to);
+        Self::deposit_event(RawEvent::Transferred(*asset_id, from.clone(), to.clone(), amount, fee));
+        Ok(amount)
+    }
+
+    /// the fee "from" pays for transferring "amount" of the asset and where it goes
+    /// the fee destination itself pays nothing
+    pub fn transfer_fee_of(
+        asset_id: &T::AssetId,
+        from: &T::AccountId,
+        amount: T::Balance,
+    ) -> (T::Balance, Option<T::AccountId>) {
+        match Self::transfer_fee(asset_id) {
+            Some(ref fee) if fee.destination != *from => {
+                let charged = fee.fee_of(amount);
+                if charged.is_zero() {
+                    (charged, None)
+                } else {
+                    (charged, Some(fee.destination.clone()))
+                }
+            }
+            _ => (Zero::zero(), None),
+        }
+    }
+
+    fn charge_transfer_fee(
+        asset_id: &T::AssetId,
+        from: &T::AccountId,
+        destination: &T::AccountId,
+        fee: T::Balance,
+    ) -> Result {
+        Self::seed_checkpoint(asset_id, destination);
+        <generic_asset::Module<T>>::make_transfer_with_event(asset_id, from, destination, fee)?;
+        Self::balance_changed(asset_id, destination);
+        Ok(())
+    }
+
+    /// returns the amount that should actually be transferred under the asset's "DustPolicy"
+    /// "fee" is what "from" pays on top of the amount
+    fn apply_dust_policy(
+        asset_id: &T::AssetId,
+        from: &T::AccountId,
+        to: &T::AccountId,
+        amount: T::Balance,
+        fee: T::Balance,
+    ) -> result::Result<T::Balance, &'static str> {
+        let free = Self::free_balance(asset_id, from);
+        let spent = amount.checked_add(&fee).ok_or("transfer overflows")?;
+        let left = free.checked_sub(&spent).ok_or("insufficient balance")?;
+        let minimum = match Self::minimum_balance(asset_id) {
+            Some(m) => m,
+            None => return Ok(amount),
+        };
+        ensure!(
+            Self::free_balance(asset_id, to).saturating_add(amount) >= minimum.amount,
+            "recipient balance would be below minimum"
+        );
+        if left.is_zero() || left >= minimum.amount {
+            return Ok(amount);
+        }
+        match minimum.policy {
+            DustPolicy::Sweep => Ok(free - fee),
+            DustPolicy::Deny => Err("transfer would leave dust"),
+        }
+    }
+
+    /// burn everything of an asset in an account, reserved balance included
+    /// the burn is made by the module account which holds the burn permission
+    /// named reservations, vesting schedules and checkpoints of the account go along with it
+    fn burn_all(asset_id: &T::AssetId, who: &T::AccountId) -> Result {
+        let reserved = <generic_asset::Module<T>>::reserved_balance(asset_id, who);
+        if !reserved.is_zero() {
+            <generic_asset::Module<T>>::unreserve(asset_id, who, reserved);
+        }
+        let amount = Self::free_balance(asset_id, who);
+        if !amount.is_zero() {
+            generic_asset::Call::<T>::burn(asset_id.clone(), who.clone(), amount)
+                .dispatch(system::RawOrigin::Signed(Self::account_id()).into())?;
+            // ignore the err
+            T::OnAssetBurn::on_asset_burn(asset_id, who, &amount).unwrap_or_default();
+        }
+        Self::balance_changed(asset_id, who);
+
+        let key = (*asset_id, who.clone());
+        for id in <ReserveIds<T>>::take(&key) {
+            <NamedReserves<T>>::remove((id, *asset_id, who.clone()));
+        }
+        <VestingSchedules<T>>::remove(&key);
+        <Checkpoints<T>>::remove(asset_id, who);
+        Ok(())
+    }
+
+    /// bookkeeping after the balance of an account has changed
+    fn balance_changed(asset_id: &T::AssetId, who: &T::AccountId) {
+        Self::update_holder(asset_id, who);
+        Self::write_checkpoint(asset_id, who);
+        Self::try_reap(asset_id, who);
+    }
+
+    /// keep the holder index of an asset in line with the balance of "who", if the index is enabled
+    fn update_holder(asset_id: &T::AssetId, who: &T::AccountId) {
+        if !Self::holder_index_enabled(asset_id) {
+            return;
+        }
+        let holding = !<generic_asset::Module<T>>::total_balance(asset_id, who).is_zero();
+        let index = Self::holder_position((*asset_id, who.clone()));
+        match (holding, index) {
+            (true, None) => {
+                let count = Self::holder_count(asset_id);
+                <Holders<T>>::insert((*asset_id, count), who.clone());
+                <HolderPositions<T>>::insert((*asset_id, who.clone()), count);
+                <HolderCount<T>>::insert(asset_id, count + 1);
+            }
+            (false, Some(i)) => {
+                // swap the last holder into the removed slot
+                let last = Self::holder_count(asset_id) - 1;
+                if i != last {
+                    let moved = <Holders<T>>::get((*asset_id, last));
+                    <Holders<T>>::insert((*asset_id, i), moved.clone());
+                    <HolderPositions<T>>::insert((*asset_id, moved), i);
+                }
+                <Holders<T>>::remove((*asset_id, last));
+                <HolderPositions<T>>::remove((*asset_id, who.clone()));
+                <HolderCount<T>>::insert(asset_id, last);
+            }
+            _ => {}
+        }
+    }
+
+    /// record the balance of "who" at the block checkpoints were enabled, before its first change since then
+    /// it must be called before the balance changes, so that the history starts with what "who" held back then
+    fn seed_checkpoint(asset_id: &T::AssetId, who: &T::AccountId) {
+        let enabled_at = match Self::checkpoints_enabled_at(asset_id) {
+            Some(at) => at,
+            None => return,
+        };
+        if <Checkpoints<T>>::exists(asset_id, who) {
+            return;
+        }
+        let balance = <generic_asset::Module<T>>::total_balance(asset_id, who);
+        <Checkpoints<T>>::insert(asset_id, who, vec![(enabled_at, balance)]);
+    }
+
+    /// record the current balance of "who", if checkpoints are enabled for the asset
+    /// there is at most one checkpoint per block, the latest balance in a block wins
+    /// the oldest checkpoint is dropped once there are more than "MAX_CHECKPOINTS"
+    fn write_checkpoint(asset_id: &T::AssetId, who: &T::AccountId) {
+        if Self::checkpoints_enabled_at(asset_id).is_none() {
+            return;
+        }
+        let now = <system::Module<T>>::block_number();
+        let balance = <generic_asset::Module<T>>::total_balance(asset_id, who);
+        <Checkpoints<T>>::mutate(asset_id, who, |v| match v.last_mut() {
+            Some(last) if last.0 == now => last.1 = balance,
+            Some(last) if last.1 == balance => {}
+            _ => {
+                v.push((now, balance));
+                if v.len() > MAX_CHECKPOINTS {
+                    v.remove(0);
+                }
+            }
+        });
+    }
+
+    /// the balance of "who" at the end of block "at"
+    /// an account without checkpoints hasn't changed its balance since checkpoints were enabled, that is its current balance
+    /// None if it's unknown: "at" is before checkpoints were enabled, or its checkpoint has been dropped
+    pub fn balance_at(asset_id: &T::AssetId, who: &T::AccountId, at: T::BlockNumber) -> Option<T::Balance> {
+        let enabled_at = Self::checkpoints_enabled_at(asset_id)?;
+        let checkpoints = Self::checkpoints(asset_id, who);
+        if checkpoints.is_empty() {
+            return if at >= enabled_at {
+                Some(<generic_asset::Module<T>>::total_balance(asset_id, who))
+            } else {
+                None
+            };
+        }
+        match checkpoints.binary_search_by(|c| c.0.cmp(&at)) {
+            Ok(i) => Some(checkpoints[i].1),
+            Err(0) => None,
+            Err(i) => Some(checkpoints[i - 1].1),
+        }
+    }
+
+    /// at most "count" holders of an asset starting from "start", in no particular order
+    pub fn holders(asset_id: &T::AssetId, start: u32, count: u32) -> Vec<T::AccountId> {
+        let end = cmp::min(start.saturating_add(count), Self::holder_count(asset_id));
+        (start..end)
+            .map(|i| <Holders<T>>::get((*asset_id, i)))
+            .collect()
+    }
+
+    /// remove an emptied account from the storage of an asset
+    fn try_reap(asset_id: &T::AssetId, who: &T::AccountId) {
+        if !<generic_asset::FreeBalance<T>>::exists(asset_id, who) {
+            return;
+        }
+        if !<generic_asset::Module<T>>::total_balance(asset_id, who).is_zero() {
+            return;
+        }
+        <generic_asset::FreeBalance<T>>::remove(asset_id, who);
+        <generic_asset::ReservedBalance<T>>::remove(asset_id, who);
+        // ignore the err
+        T::OnAssetReaped::on_asset_reaped(asset_id, who).unwrap_or_default();
+        Self::deposit_event(RawEvent::Reaped(*asset_id, who.clone()));
+    }
+
+    /// all the checks happen before any balance moves, so the hooks see the same state as a single transfer
+    /// recipients must be distinct, so that e.g. saving's collection account receives at most one leg per batch
+    /// dust swept by the asset's "DustPolicy" goes along with the last leg
+    pub fn make_batch_transfer(
+        asset_id: &T::AssetId,
+        from: &T::AccountId,
+        legs: &[(T::AccountId, T::Balance)],
+    ) -> Result {
+        ensure!(!legs.is_empty(), "empty batch");
+        Self::ensure_not_frozen(asset_id, from)?;
+
+        let mut total = T::Balance::zero();
+        let mut fees = T::Balance::zero();
+        let mut leg_fees = Vec::with_capacity(legs.len());
+        let mut destination = None;
+        let mut recipients: Vec<&T::AccountId> = Vec::with_capacity(legs.len());
+        for (to, amount) in legs {
+            ensure!(to != from, "can't transfer to self");
+            ensure!(!recipients.contains(&to), "duplicated recipient in batch");
+            recipients.push(to);
+            let (fee, d) = Self::transfer_fee_of(asset_id, from, *amount);
+            destination = destination.or(d);
+            leg_fees.push(fee);
+            fees = fees.checked_add(&fee).ok_or("batch total overflows")?;
+            total = total
+                .checked_add(amount)
+                .and_then(|t| t.checked_add(&fee))
+                .ok_or("batch total overflows")?;
+        }
+        let free = Self::free_balance(asset_id, from);
+        ensure!(free >= total, "insufficient balance for batch");
+        let mut legs = legs.to_vec();
+        if let Some(minimum) = Self::minimum_balance(asset_id) {
+            let left = free - total;
+            if !left.is_zero() && left < minimum.amount {
+                match minimum.policy {
+                    DustPolicy::Sweep => {
+                        if let Some(last) = legs.last_mut() {
+                            last.1 = last.1 + left;
+                        }
+                    }
+                    DustPolicy::Deny => return Err("transfer would leave dust"),
+                }
+            }
+            for (to, amount) in &legs {
+                ensure!(
+                    Self::free_balance(asset_id, to).saturating_add(*amount) >= minimum.amount,
+                    "recipient balance would be below minimum"
+                );
+            }
+        }
+        for (to, amount) in &legs {
+            T::BeforeAssetTransfer::before_asset_transfer(asset_id, from, to, amount)?;
+        }
+        if let Some(destination) = &destination {
+            T::BeforeAssetTransfer::before_asset_transfer(asset_id, from, destination, &fees)?;
+        }
+
+        Self::seed_checkpoint(asset_id, from);
+        for (to, _) in &legs {
+            Self::seed_checkpoint(asset_id, to);
+        }
+        for (i, (to, amount)) in legs.iter().enumerate() {
+            <generic_asset::Module<T>>::make_transfer_with_event(asset_id, from, to, *amount)
+                .or_else(|err| -> Result {
+                    // revert the legs already done
+                    for (to, amount) in &legs[..i] {
+                        <generic_asset::Module<T>>::make_transfer_with_event(
+                            asset_id, to, from, *amount,
+                        )?;
+                    }
+                    Err(err)
+                })?;
+        }
+        if let Some(destination) = &destination {
+            Self::charge_transfer_fee(asset_id, from, destination, fees).or_else(|err| -> Result {
+                for (to, amount) in &legs {
+                    <generic_asset::Module<T>>::make_transfer_with_event(asset_id, to, from, *amount)?;
+                }
+                Err(err)
+            })?;
+        }
+
+        for ((to, amount), fee) in legs.iter().zip(leg_fees) {
+            // ignore the err
+            T::OnAssetTransfer::on_asset_transfer(asset_id, from, to, amount).unwrap_or_default();
+            Self::balance_changed(asset_id, to);
+            Self::deposit_event(RawEvent::Transferred(*asset_id, from.clone(), to.clone(), *amount, fee));
+        }
+        if let Some(destination) = &destination {
+            // ignore the err
+            T::OnAssetTransfer::on_asset_transfer(asset_id, from, destination, &fees).unwrap_or_default();
+        }
+        Self::balance_changed(asset_id, from);
+        Ok(())
+    }
+
     pub fn make_transfer(
         asset_id: &T::AssetId,
         from: &T::AccountId,
         to: &T::AccountId,
         amount: T::Balance,
     ) -> Result {
-        <generic_asset::Module<T>>::make_transfer(asset_id, from, to, amount)
+        Self::ensure_not_frozen(asset_id, from)?;
+        Self::seed_checkpoint(asset_id, from);
+        Self::seed_checkpoint(asset_id, to);
+        <generic_asset::Module<T>>::make_transfer(asset_id, from, to, amount)?;
+        Self::balance_changed(asset_id, from);
+        Self::balance_changed(asset_id, to);
+        Ok(())
     }
 
+    /// use up "amount" of the allowance "owner" has approved to "spender"
+    pub fn spend_allowance(
+        asset_id: &T::AssetId,
+        owner: &T::AccountId,
+        spender: &T::AccountId,
+        amount: T::Balance,
+    ) -> Result {
+        let key = (owner.clone(), spender.clone());
+        let allowance = Self::allowance(asset_id, &key);
+        ensure!(allowance >= amount, "allowance is not enough");
+        let left = allowance - amount;
+        if left.is_zero() {
+            <Allowances<T>>::remove(asset_id, &key);
+        } else {
+            <Allowances<T>>::insert(asset_id, &key, left);
+        }
+        Ok(())
+    }
+
+    /// the transfer path of other modules, e.g. loan, saving and the AMM
+    /// it goes through T::BeforeAssetTransfer like a user initiated transfer, but pays no transfer fee
     pub fn make_transfer_with_event(
         asset_id: &T::AssetId,
         from: &T::AccountId,
         to: &T::AccountId,
         amount: T::Balance,
     ) -> Result {
-        <generic_asset::Module<T>>::make_transfer_with_event(asset_id, from, to, amount)
+        Self::ensure_not_frozen(asset_id, from)?;
+        T::BeforeAssetTransfer::before_asset_transfer(asset_id, from, to, &amount)?;
+        Self::seed_checkpoint(asset_id, from);
+        Self::seed_checkpoint(asset_id, to);
+        <generic_asset::Module<T>>::make_transfer_with_event(asset_id, from, to, amount)?;
+        Self::balance_changed(asset_id, from);
+        Self::balance_changed(asset_id, to);
+        Ok(())
     }
 }
 
@@ -273,10 +1632,38 @@ decl_event!(
     pub enum Event<T>
     where
         AccountId = <T as system::Trait>::AccountId,
+        AssetId = <T as generic_asset::Trait>::AssetId,
+        Balance = <T as generic_asset::Trait>::Balance,
+        Deposit = BalanceOf<T>,
     {
         // Just a dummy event.
         // Event `Something` is declared with a parameter of the type `u32` and `AccountId`
         // To emit this event, we call the deposit funtion, from our runtime funtions
         PhantomEvent(u32, AccountId),
+
+        /// (asset id, owner, reserved deposit)
+        Created(AssetId, AccountId, Deposit),
+        /// (asset id, owner, refunded deposit)
+        DepositRefunded(AssetId, AccountId, Deposit),
+        Frozen(AssetId, AccountId),
+        Thawed(AssetId, AccountId),
+        /// (asset id, owner, spender, allowance)
+        Approved(AssetId, AccountId, AccountId, Balance),
+        /// a user initiated transfer (asset id, from, to, amount, fee)
+        Transferred(AssetId, AccountId, AccountId, Balance, Balance),
+        /// an emptied account is removed from the storage of an asset
+        Reaped(AssetId, AccountId),
+        /// (reservation id, asset id, account, amount)
+        ReservedNamed(ReserveIdentifier, AssetId, AccountId, Balance),
+        UnreservedNamed(ReserveIdentifier, AssetId, AccountId, Balance),
+        /// (reservation id, asset id, from, to, amount)
+        RepatriatedNamed(ReserveIdentifier, AssetId, AccountId, AccountId, Balance),
+        /// (asset id, account, vesting balance)
+        Vested(AssetId, AccountId, Balance),
+        VestingClaimed(AssetId, AccountId, Balance),
+        DestroyStarted(AssetId),
+        /// a holder burnt its balance of a destroying asset (asset id, holder, amount)
+        Redeemed(AssetId, AccountId, Balance),
+        Destroyed(AssetId),
     }
 );
@@ -67,24 +67,27 @@ impl balances::Trait for TestRuntime {
     type TransactionByteFee = TransactionByteFee;
     type WeightToFee = ();
 }
-impl sudo::Trait for TestRuntime {
-    type Event = ();
-
-    // this is a wild guess ^_^
-    type Proposal = Call<TestRuntime>;
-}
-
 impl generic_asset::Trait for TestRuntime {
     type Event = ();
     type Balance = u128;
     type AssetId = u32;
 }
+parameter_types! {
+    pub const CreationDeposit: u128 = 100;
+    pub const CreationCooldown: u64 = 10;
+}
 impl Trait for TestRuntime {
     type Event = ();
+    type AdminOrigin = system::EnsureRoot<u64>;
+    type Currency = balances::Module<TestRuntime>;
+    type CreationDeposit = CreationDeposit;
+    type CreationCooldown = CreationCooldown;
     type OnAssetMint = ();
     type OnAssetCreate = ();
     type OnAssetTransfer = ();
     type OnAssetBurn = ();
+    type OnAssetReaped = ();
+    type BeforeAssetDestroy = ();
     type BeforeAssetMint = ();
     type BeforeAssetCreate = ();
     type BeforeAssetTransfer = ();
@@ -92,6 +95,12 @@ impl Trait for TestRuntime {
 }
 
 pub type Assets = Module<TestRuntime>;
+pub type Balances = balances::Module<TestRuntime>;
+pub type System = system::Module<TestRuntime>;
+
+pub const ROOT: u64 = 1;
+pub const ALICE: u64 = 2;
+pub const BOB: u64 = 3;
 
 // This function basically just builds a genesis storage key/value store according to
 // our desired mockup.
@@ -100,12 +109,25 @@ pub fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
         .build_storage::<TestRuntime>()
         .unwrap();
 
-    sudo::GenesisConfig::<TestRuntime> { key: 1 }
-        .assimilate_storage(&mut t)
-        .unwrap();
+    generic_asset::GenesisConfig::<TestRuntime> {
+        next_asset_id: 9,
+        staking_asset_id: 0,
+        spending_asset_id: 0,
+        assets: vec![],
+        initial_balance: 0,
+        endowed_accounts: vec![],
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+
+    balances::GenesisConfig::<TestRuntime> {
+        balances: vec![(ALICE, 1000), (BOB, 50)],
+        vesting: vec![],
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
 
     // We use default for brevity, but you can configure as desired if needed.
-    // balances::GenesisConfig::<Test>::default().assimilate_storage(&mut t).unwrap();
     // GenesisConfig::<TestRuntime> {
     //     phase_infos: vec![
     //         (PHASE1_QUOTA, PHASE1_EXCHANGE),
@@ -22,3 +22,661 @@ use mock::*;
 fn unittest_works() {
     dbg!("hello world");
 }
+
+#[test]
+fn create_with_deposit_works() {
+    with_externalities(&mut new_test_ext(), || {
+        let asset_id = Assets::get_current_asset_id();
+        assert_ok!(Assets::create_with_deposit(
+            Origin::signed(ALICE),
+            1000,
+            "DEX".as_bytes().to_vec()
+        ));
+        assert_eq!(Assets::owner(&asset_id), Some(ALICE));
+        assert_eq!(Assets::symbols(&asset_id), "DEX".as_bytes().to_vec());
+        assert_eq!(Assets::free_balance(&asset_id, &ALICE), 1000);
+        assert_eq!(Assets::creation_deposit(&asset_id), Some((ALICE, 100)));
+        assert_eq!(Balances::reserved_balance(&ALICE), 100);
+
+        // the owner mints through this module only, within the limits of the asset
+        assert!(<generic_asset::Module<TestRuntime>>::mint(
+            Origin::signed(ALICE),
+            asset_id,
+            ALICE,
+            1
+        )
+        .is_err());
+        assert_ok!(Assets::set_supply_cap(Origin::signed(ALICE), asset_id, 1500));
+        assert_ok!(Assets::mint(Origin::signed(ALICE), asset_id, BOB, 500));
+        assert_noop!(
+            Assets::mint(Origin::signed(ALICE), asset_id, BOB, 1),
+            "exceed mint limits"
+        );
+        assert_noop!(
+            Assets::mint(Origin::signed(BOB), asset_id, BOB, 1),
+            "asset owner only"
+        );
+
+        Assets::refund_creation_deposit(&asset_id);
+        assert_eq!(Balances::reserved_balance(&ALICE), 0);
+        assert_eq!(Assets::creation_deposit(&asset_id), None);
+    });
+}
+
+#[test]
+fn create_with_deposit_is_rate_limited() {
+    with_externalities(&mut new_test_ext(), || {
+        System::set_block_number(1);
+        assert_ok!(Assets::create_with_deposit(
+            Origin::signed(ALICE),
+            0,
+            "ONE".as_bytes().to_vec()
+        ));
+        assert_noop!(
+            Assets::create_with_deposit(Origin::signed(ALICE), 0, "TWO".as_bytes().to_vec()),
+            "asset creation is rate limited"
+        );
+        System::set_block_number(11);
+        assert_noop!(
+            Assets::create_with_deposit(Origin::signed(ALICE), 0, "ONE".as_bytes().to_vec()),
+            "symbol is already taken"
+        );
+        assert_noop!(
+            Assets::create(Origin::ROOT, 0, "ONE".as_bytes().to_vec()),
+            "symbol is already taken"
+        );
+        assert_ok!(Assets::create_with_deposit(
+            Origin::signed(ALICE),
+            0,
+            "TWO".as_bytes().to_vec()
+        ));
+
+        // BOB can't afford the deposit
+        assert!(
+            Assets::create_with_deposit(Origin::signed(BOB), 0, "BOB".as_bytes().to_vec())
+                .is_err()
+        );
+    });
+}
+
+#[test]
+fn freeze_and_thaw_works() {
+    with_externalities(&mut new_test_ext(), || {
+        let asset_id = Assets::get_current_asset_id();
+        assert_ok!(Assets::create_with_deposit(
+            Origin::signed(ALICE),
+            1000,
+            "DEX".as_bytes().to_vec()
+        ));
+
+        assert_noop!(
+            Assets::freeze(Origin::signed(BOB), asset_id, ALICE),
+            "asset owner only"
+        );
+        assert_ok!(Assets::freeze(Origin::ROOT, asset_id, ALICE));
+        assert_noop!(
+            Assets::transfer(Origin::signed(ALICE), asset_id, BOB, 10),
+            "account is frozen for this asset"
+        );
+        assert_noop!(
+            Assets::make_transfer_with_event(&asset_id, &ALICE, &BOB, 10),
+            "account is frozen for this asset"
+        );
+
+        assert_ok!(Assets::thaw(Origin::signed(ALICE), asset_id, ALICE));
+        assert_ok!(Assets::transfer(Origin::signed(ALICE), asset_id, BOB, 10));
+        assert_eq!(Assets::free_balance(&asset_id, &BOB), 10);
+    });
+}
+
+#[test]
+fn approve_and_transfer_from_works() {
+    with_externalities(&mut new_test_ext(), || {
+        let asset_id = Assets::get_current_asset_id();
+        assert_ok!(Assets::create_with_deposit(
+            Origin::signed(ALICE),
+            1000,
+            "DEX".as_bytes().to_vec()
+        ));
+
+        assert_ok!(Assets::approve(Origin::signed(ALICE), asset_id, BOB, 100));
+        assert_eq!(Assets::allowance(asset_id, (ALICE, BOB)), 100);
+        assert_noop!(
+            Assets::transfer_from(Origin::signed(BOB), asset_id, ALICE, ROOT, 101),
+            "allowance is not enough"
+        );
+
+        assert_ok!(Assets::transfer_from(
+            Origin::signed(BOB),
+            asset_id,
+            ALICE,
+            ROOT,
+            60
+        ));
+        assert_eq!(Assets::free_balance(&asset_id, &ROOT), 60);
+        assert_eq!(Assets::free_balance(&asset_id, &ALICE), 940);
+        assert_eq!(Assets::allowance(asset_id, (ALICE, BOB)), 40);
+
+        assert_ok!(Assets::approve(Origin::signed(ALICE), asset_id, BOB, 0));
+        assert_eq!(Assets::allowance(asset_id, (ALICE, BOB)), 0);
+    });
+}
+
+#[test]
+fn batch_transfer_works() {
+    with_externalities(&mut new_test_ext(), || {
+        let asset_id = Assets::get_current_asset_id();
+        assert_ok!(Assets::create_with_deposit(
+            Origin::signed(ALICE),
+            1000,
+            "DEX".as_bytes().to_vec()
+        ));
+
+        assert_ok!(Assets::batch_transfer(
+            Origin::signed(ALICE),
+            asset_id,
+            vec![(BOB, 100), (ROOT, 200)]
+        ));
+        assert_eq!(Assets::free_balance(&asset_id, &ALICE), 700);
+        assert_eq!(Assets::free_balance(&asset_id, &BOB), 100);
+        assert_eq!(Assets::free_balance(&asset_id, &ROOT), 200);
+
+        // nothing moves if the whole batch can't be paid
+        assert_noop!(
+            Assets::batch_transfer(
+                Origin::signed(ALICE),
+                asset_id,
+                vec![(BOB, 500), (ROOT, 500)]
+            ),
+            "insufficient balance for batch"
+        );
+        assert_noop!(
+            Assets::batch_transfer(Origin::signed(ALICE), asset_id, vec![(BOB, 1), (BOB, 1)]),
+            "duplicated recipient in batch"
+        );
+    });
+}
+
+#[test]
+fn mint_limits_work() {
+    with_externalities(&mut new_test_ext(), || {
+        let asset_id = Assets::get_current_asset_id();
+        assert_ok!(Assets::create(Origin::ROOT, 0, "TBD".as_bytes().to_vec()));
+        assert_eq!(Assets::mint_headroom(&asset_id), None);
+
+        assert_ok!(Assets::set_supply_cap(Origin::ROOT, asset_id, 1000));
+        assert_ok!(Assets::set_mint_limit(Origin::ROOT, asset_id, 10, 300));
+        assert_eq!(Assets::mint_headroom(&asset_id), Some(300));
+
+        System::set_block_number(1);
+        assert_ok!(Assets::mint(Origin::ROOT, asset_id, ALICE, 200));
+        assert_eq!(Assets::mint_headroom(&asset_id), Some(100));
+        assert_noop!(
+            Assets::mint(Origin::ROOT, asset_id, ALICE, 101),
+            "exceed mint limits"
+        );
+
+        // a new window starts
+        System::set_block_number(11);
+        assert_eq!(Assets::mint_headroom(&asset_id), Some(300));
+        assert_ok!(Assets::mint(Origin::ROOT, asset_id, ALICE, 300));
+        System::set_block_number(21);
+        assert_ok!(Assets::mint(Origin::ROOT, asset_id, ALICE, 300));
+
+        // the supply cap is reached
+        System::set_block_number(31);
+        assert_eq!(Assets::mint_headroom(&asset_id), Some(200));
+        assert_noop!(
+            Assets::mint(Origin::ROOT, asset_id, ALICE, 201),
+            "exceed mint limits"
+        );
+    });
+}
+
+#[test]
+fn minimum_balance_works() {
+    with_externalities(&mut new_test_ext(), || {
+        let asset_id = Assets::get_current_asset_id();
+        assert_ok!(Assets::create_with_deposit(
+            Origin::signed(ALICE),
+            1000,
+            "DEX".as_bytes().to_vec()
+        ));
+        assert_ok!(Assets::set_minimum_balance(
+            Origin::signed(ALICE),
+            asset_id,
+            10,
+            DustPolicy::Deny
+        ));
+
+        assert_noop!(
+            Assets::transfer(Origin::signed(ALICE), asset_id, BOB, 9),
+            "recipient balance would be below minimum"
+        );
+        assert_noop!(
+            Assets::transfer(Origin::signed(ALICE), asset_id, BOB, 995),
+            "transfer would leave dust"
+        );
+
+        assert_ok!(Assets::set_minimum_balance(
+            Origin::signed(ALICE),
+            asset_id,
+            10,
+            DustPolicy::Sweep
+        ));
+        assert_ok!(Assets::transfer(Origin::signed(ALICE), asset_id, BOB, 995));
+        assert_eq!(Assets::free_balance(&asset_id, &BOB), 1000);
+        assert!(!<generic_asset::FreeBalance<TestRuntime>>::exists(
+            &asset_id, &ALICE
+        ));
+
+        // the dust swept along is charged to the allowance
+        assert_ok!(Assets::approve(Origin::signed(BOB), asset_id, ROOT, 995));
+        assert_noop!(
+            Assets::transfer_from(Origin::signed(ROOT), asset_id, BOB, ALICE, 995),
+            "allowance is not enough"
+        );
+        assert_ok!(Assets::approve(Origin::signed(BOB), asset_id, ROOT, 1000));
+        assert_ok!(Assets::transfer_from(
+            Origin::signed(ROOT),
+            asset_id,
+            BOB,
+            ALICE,
+            995
+        ));
+        assert_eq!(Assets::free_balance(&asset_id, &ALICE), 1000);
+        assert_eq!(Assets::allowance(asset_id, (BOB, ROOT)), 0);
+
+        // and goes along with the last leg of a batch
+        assert_ok!(Assets::batch_transfer(
+            Origin::signed(ALICE),
+            asset_id,
+            vec![(BOB, 500), (ROOT, 495)]
+        ));
+        assert_eq!(Assets::free_balance(&asset_id, &BOB), 500);
+        assert_eq!(Assets::free_balance(&asset_id, &ROOT), 500);
+        assert_eq!(Assets::free_balance(&asset_id, &ALICE), 0);
+    });
+}
+
+#[test]
+fn destroy_works() {
+    with_externalities(&mut new_test_ext(), || {
+        let asset_id = Assets::get_current_asset_id();
+        assert_ok!(Assets::create_with_deposit(
+            Origin::signed(ALICE),
+            1000,
+            "DEX".as_bytes().to_vec()
+        ));
+        assert_ok!(Assets::enable_checkpoints(Origin::signed(ALICE), asset_id));
+        assert_ok!(Assets::transfer(Origin::signed(ALICE), asset_id, BOB, 400));
+        assert_ok!(Assets::reserve_named(b"loan0001", &asset_id, &BOB, 100));
+        assert_ok!(Assets::vest(Origin::signed(ALICE), asset_id, BOB, 100, 10, 5, 2));
+        assert_ok!(Assets::set_transfer_fee(
+            Origin::signed(ALICE),
+            asset_id,
+            Some(TransferFee { kind: FeeKind::Flat(1), destination: ROOT })
+        ));
+        assert_ok!(Assets::freeze(Origin::signed(ALICE), asset_id, ROOT));
+        assert_ok!(Assets::approve(Origin::signed(ALICE), asset_id, BOB, 10));
+
+        assert_noop!(
+            Assets::finish_destroy(Origin::signed(ALICE), asset_id),
+            "asset is not being destroyed"
+        );
+        assert_ok!(Assets::start_destroy(Origin::signed(ALICE), asset_id));
+        assert_noop!(
+            Assets::transfer(Origin::signed(ALICE), asset_id, BOB, 1),
+            "asset is being destroyed"
+        );
+
+        assert_ok!(Assets::destroy_accounts(
+            Origin::signed(ALICE),
+            asset_id,
+            vec![ALICE]
+        ));
+        assert_noop!(
+            Assets::finish_destroy(Origin::signed(ALICE), asset_id),
+            "asset still has balances"
+        );
+        assert_ok!(Assets::destroy_accounts(
+            Origin::signed(ALICE),
+            asset_id,
+            vec![BOB]
+        ));
+        assert_ok!(Assets::finish_destroy(Origin::signed(ALICE), asset_id));
+
+        assert!(!Assets::asset_exists(&asset_id));
+        assert_eq!(Assets::owner(&asset_id), None);
+        assert_eq!(Assets::asset_id_of_symbol("DEX".as_bytes().to_vec()), None);
+        assert_eq!(Assets::transfer_fee(&asset_id), None);
+        assert_eq!(Assets::checkpoints_enabled_at(&asset_id), None);
+        assert!(Assets::checkpoints(asset_id, BOB).is_empty());
+        assert!(Assets::reserve_ids((asset_id, BOB)).is_empty());
+        assert_eq!(Assets::named_reserve((*b"loan0001", asset_id, BOB)), 0);
+        assert_eq!(Assets::named_reserve((VESTING_ID, asset_id, BOB)), 0);
+        assert!(Assets::vesting_schedules((asset_id, BOB)).is_empty());
+        assert!(!Assets::is_frozen(asset_id, ROOT));
+        assert_eq!(Assets::allowance(asset_id, (ALICE, BOB)), 0);
+        assert_eq!(Balances::reserved_balance(&ALICE), 0);
+        // the id can't be minted again
+        assert_noop!(Assets::make_mint(&asset_id, &ALICE, 1), "asset is destroyed");
+    });
+}
+
+#[test]
+fn destroy_reserved_asset_works() {
+    with_externalities(&mut new_test_ext(), || {
+        let asset_id = 5;
+        let options = |burn| AssetOptions {
+            initial_issuance: 0,
+            permissions: PermissionLatest {
+                update: generic_asset::Owner::Address(Assets::account_id()),
+                mint: generic_asset::Owner::Address(Assets::account_id()),
+                burn: generic_asset::Owner::Address(burn),
+            },
+        };
+        assert_noop!(
+            Assets::create_reserved(Origin::ROOT, asset_id, options(ALICE), b"LP".to_vec()),
+            "burn permission must stay with the module account"
+        );
+        assert_ok!(Assets::create_reserved(
+            Origin::ROOT,
+            asset_id,
+            options(Assets::account_id()),
+            b"LP".to_vec()
+        ));
+        assert!(Assets::asset_exists(&asset_id));
+        assert_eq!(Assets::owner(&asset_id), Some(Assets::account_id()));
+        assert_ok!(Assets::make_mint(&asset_id, &ALICE, 100));
+        assert_ok!(Assets::make_mint(&asset_id, &BOB, 50));
+
+        assert_noop!(
+            Assets::redeem_destroying(Origin::signed(ALICE), asset_id),
+            "asset is not being destroyed"
+        );
+        assert_ok!(Assets::start_destroy(Origin::ROOT, asset_id));
+        // holders can burn their balances themselves
+        assert_ok!(Assets::redeem_destroying(Origin::signed(ALICE), asset_id));
+        assert_eq!(Assets::free_balance(&asset_id, &ALICE), 0);
+        assert_noop!(
+            Assets::redeem_destroying(Origin::signed(ALICE), asset_id),
+            "nothing to redeem"
+        );
+        assert_ok!(Assets::destroy_accounts(Origin::ROOT, asset_id, vec![BOB]));
+        assert_ok!(Assets::finish_destroy(Origin::ROOT, asset_id));
+        assert!(!Assets::asset_exists(&asset_id));
+        assert_noop!(
+            Assets::create_reserved(Origin::ROOT, asset_id, options(Assets::account_id()), b"LP".to_vec()),
+            "asset is destroyed"
+        );
+    });
+}
+
+#[test]
+fn holder_index_works() {
+    with_externalities(&mut new_test_ext(), || {
+        let asset_id = Assets::get_current_asset_id();
+        assert_ok!(Assets::create(Origin::ROOT, 0, "DEX".as_bytes().to_vec()));
+        assert_ok!(Assets::enable_holder_index(Origin::ROOT, asset_id));
+
+        assert_ok!(Assets::mint(Origin::ROOT, asset_id, ALICE, 100));
+        assert_ok!(Assets::transfer(Origin::signed(ALICE), asset_id, BOB, 40));
+        assert_eq!(Assets::holder_count(&asset_id), 2);
+        assert_eq!(Assets::holders(&asset_id, 0, 10), vec![ALICE, BOB]);
+        assert_eq!(Assets::holders(&asset_id, 1, 10), vec![BOB]);
+
+        assert_ok!(Assets::transfer(Origin::signed(ALICE), asset_id, BOB, 60));
+        assert_eq!(Assets::holder_count(&asset_id), 1);
+        assert_eq!(Assets::holders(&asset_id, 0, 10), vec![BOB]);
+
+        assert_ok!(Assets::burn(Origin::ROOT, asset_id, BOB, 100));
+        assert_eq!(Assets::holder_count(&asset_id), 0);
+    });
+}
+
+#[test]
+fn balance_checkpoints_work() {
+    with_externalities(&mut new_test_ext(), || {
+        let asset_id = Assets::get_current_asset_id();
+        assert_ok!(Assets::create(Origin::ROOT, 0, "DEX".as_bytes().to_vec()));
+        assert_ok!(Assets::mint(Origin::ROOT, asset_id, ROOT, 70));
+        System::set_block_number(1);
+        assert_ok!(Assets::enable_checkpoints(Origin::ROOT, asset_id));
+
+        assert_ok!(Assets::mint(Origin::ROOT, asset_id, ALICE, 100));
+        System::set_block_number(5);
+        assert_ok!(Assets::transfer(Origin::signed(ALICE), asset_id, BOB, 30));
+        assert_ok!(Assets::transfer(Origin::signed(ALICE), asset_id, BOB, 20));
+        System::set_block_number(9);
+        assert_ok!(Assets::transfer(Origin::signed(BOB), asset_id, ALICE, 50));
+
+        // unknown before checkpoints were enabled
+        assert_eq!(Assets::balance_at(&asset_id, &ALICE, 0), None);
+        assert_eq!(Assets::balance_at(&asset_id, &ALICE, 1), Some(100));
+        assert_eq!(Assets::balance_at(&asset_id, &ALICE, 4), Some(100));
+        assert_eq!(Assets::balance_at(&asset_id, &ALICE, 5), Some(50));
+        assert_eq!(Assets::balance_at(&asset_id, &ALICE, 100), Some(100));
+        assert_eq!(Assets::balance_at(&asset_id, &BOB, 4), Some(0));
+        assert_eq!(Assets::balance_at(&asset_id, &BOB, 8), Some(50));
+        assert_eq!(Assets::checkpoints(asset_id, ALICE).len(), 3);
+        // never checkpointed, the balance is the same since checkpoints were enabled
+        assert_eq!(Assets::balance_at(&asset_id, &ROOT, 8), Some(70));
+        assert_eq!(Assets::balance_at(&asset_id, &ROOT, 0), None);
+
+        // the oldest checkpoints are dropped
+        for n in 0..MAX_CHECKPOINTS as u64 {
+            System::set_block_number(10 + n);
+            assert_ok!(Assets::mint(Origin::ROOT, asset_id, ALICE, 1));
+        }
+        let checkpoints = Assets::checkpoints(asset_id, ALICE);
+        assert_eq!(checkpoints.len(), MAX_CHECKPOINTS);
+        assert_eq!(checkpoints[0], (10, 101));
+        assert_eq!(Assets::balance_at(&asset_id, &ALICE, 9), None);
+        assert_eq!(Assets::balance_at(&asset_id, &ALICE, 10), Some(101));
+
+        // the first change records the balance held when checkpoints were enabled
+        System::set_block_number(200);
+        assert_ok!(Assets::transfer(Origin::signed(ROOT), asset_id, BOB, 20));
+        assert_eq!(Assets::checkpoints(asset_id, ROOT), vec![(1, 70), (200, 50)]);
+        assert_eq!(Assets::balance_at(&asset_id, &ROOT, 8), Some(70));
+        assert_eq!(Assets::balance_at(&asset_id, &ROOT, 200), Some(50));
+    });
+}
+
+#[test]
+fn named_reserves_work() {
+    with_externalities(&mut new_test_ext(), || {
+        let asset_id = Assets::get_current_asset_id();
+        let loan_1 = *b"loan0001";
+        let loan_2 = *b"loan0002";
+        assert_ok!(Assets::create(Origin::ROOT, 0, "SBTC".as_bytes().to_vec()));
+        assert_ok!(Assets::mint(Origin::ROOT, asset_id, ALICE, 100));
+
+        assert_ok!(Assets::reserve_named(&loan_1, &asset_id, &ALICE, 30));
+        assert_ok!(Assets::reserve_named(&loan_2, &asset_id, &ALICE, 50));
+        assert!(Assets::reserve_named(&loan_2, &asset_id, &ALICE, 21).is_err());
+        assert_eq!(Assets::free_balance(&asset_id, &ALICE), 20);
+        assert_eq!(Assets::named_reserve((loan_1, asset_id, ALICE)), 30);
+
+        // can't take more than what is reserved under the id
+        assert_eq!(Assets::unreserve_named(&loan_1, &asset_id, &ALICE, 40), 30);
+        assert_eq!(Assets::named_reserve((loan_1, asset_id, ALICE)), 0);
+        assert_eq!(Assets::free_balance(&asset_id, &ALICE), 50);
+
+        assert_eq!(
+            Assets::repatriate_reserved_named(&loan_2, &asset_id, &ALICE, &BOB, 20),
+            20
+        );
+        assert_eq!(Assets::free_balance(&asset_id, &BOB), 20);
+        assert_eq!(Assets::named_reserve((loan_2, asset_id, ALICE)), 30);
+        assert_eq!(
+            <generic_asset::Module<TestRuntime>>::reserved_balance(&asset_id, &ALICE),
+            30
+        );
+    });
+}
+
+#[test]
+fn vesting_works() {
+    with_externalities(&mut new_test_ext(), || {
+        let asset_id = Assets::get_current_asset_id();
+        assert_ok!(Assets::create(Origin::ROOT, 0, "SBTC".as_bytes().to_vec()));
+
+        assert_noop!(
+            Assets::vest(Origin::signed(BOB), asset_id, ALICE, 10, 1, 1, 1),
+            "asset owner only"
+        );
+        assert_noop!(
+            Assets::vest(Origin::ROOT, asset_id, ALICE, 2, 10, 5, 3),
+            "total is less than a unit per term"
+        );
+        assert_noop!(
+            Assets::vest(Origin::ROOT, asset_id, ALICE, 100_000, 10, 5, MAX_VESTING_TERMS + 1),
+            "too many terms"
+        );
+        // the admin mints the schedule, 3 terms of 33, the remainder goes with the last term
+        assert_ok!(Assets::vest(Origin::ROOT, asset_id, ALICE, 100, 10, 5, 3));
+        assert_eq!(Assets::vesting_balance(&asset_id, &ALICE), 100);
+        assert_eq!(Assets::free_balance(&asset_id, &ALICE), 0);
+        assert_eq!(Assets::total_issuance(&asset_id), 100);
+        assert_ok!(Assets::set_supply_cap(Origin::ROOT, asset_id, 150));
+        assert_noop!(
+            Assets::vest(Origin::ROOT, asset_id, ALICE, 100, 10, 5, 3),
+            "exceed mint limits"
+        );
+        assert!(Assets::transfer(Origin::signed(ALICE), asset_id, BOB, 1).is_err());
+
+        System::set_block_number(9);
+        assert_noop!(
+            Assets::claim_vested(Origin::signed(ALICE), asset_id),
+            "nothing to claim"
+        );
+
+        System::set_block_number(15);
+        assert_ok!(Assets::claim_vested(Origin::signed(ALICE), asset_id));
+        assert_eq!(Assets::free_balance(&asset_id, &ALICE), 66);
+        assert_eq!(Assets::vesting_balance(&asset_id, &ALICE), 34);
+        assert_ok!(Assets::transfer(Origin::signed(ALICE), asset_id, BOB, 66));
+
+        System::set_block_number(100);
+        assert_ok!(Assets::claim_vested(Origin::signed(ALICE), asset_id));
+        assert_eq!(Assets::free_balance(&asset_id, &ALICE), 34);
+        assert!(!<VestingSchedules<TestRuntime>>::exists((asset_id, ALICE)));
+    });
+}
+
+#[test]
+fn transfer_fee_works() {
+    with_externalities(&mut new_test_ext(), || {
+        let asset_id = Assets::get_current_asset_id();
+        let pool = 4;
+        assert_ok!(Assets::create(Origin::ROOT, 0, "SBTC".as_bytes().to_vec()));
+        assert_ok!(Assets::mint(Origin::ROOT, asset_id, ALICE, 10000));
+
+        assert_noop!(
+            Assets::set_transfer_fee(
+                Origin::ROOT,
+                asset_id,
+                Some(TransferFee { kind: FeeKind::BasisPoints(10001), destination: pool })
+            ),
+            "invalid transfer fee"
+        );
+        assert_ok!(Assets::set_transfer_fee(
+            Origin::ROOT,
+            asset_id,
+            Some(TransferFee { kind: FeeKind::BasisPoints(100), destination: pool })
+        ));
+        assert_ok!(Assets::transfer(Origin::signed(ALICE), asset_id, BOB, 1000));
+        assert_eq!(Assets::free_balance(&asset_id, &BOB), 1000);
+        assert_eq!(Assets::free_balance(&asset_id, &pool), 10);
+        let fee = TransferFee { kind: FeeKind::BasisPoints(100), destination: pool };
+        assert_eq!(fee.fee_of(u128::max_value()), u128::max_value() / 100);
+        assert_eq!(Assets::free_balance(&asset_id, &ALICE), 8990);
+        // the fee comes on top of the amount
+        assert_noop!(
+            Assets::transfer(Origin::signed(BOB), asset_id, ALICE, 1000),
+            "insufficient balance"
+        );
+        // the fee is charged to the allowance too
+        assert_ok!(Assets::approve(Origin::signed(BOB), asset_id, ROOT, 504));
+        assert_noop!(
+            Assets::transfer_from(Origin::signed(ROOT), asset_id, BOB, ALICE, 500),
+            "allowance is not enough"
+        );
+        assert_ok!(Assets::approve(Origin::signed(BOB), asset_id, ROOT, 505));
+        assert_ok!(Assets::transfer_from(Origin::signed(ROOT), asset_id, BOB, ALICE, 500));
+        assert_eq!(Assets::allowance(asset_id, (BOB, ROOT)), 0);
+        assert_eq!(Assets::free_balance(&asset_id, &pool), 15);
+        assert_eq!(Assets::free_balance(&asset_id, &ALICE), 9490);
+
+        // no fee below 500, 2% from 500, 1% from 5000
+        assert_ok!(Assets::set_transfer_fee(
+            Origin::ROOT,
+            asset_id,
+            Some(TransferFee {
+                kind: FeeKind::Tiered(vec![(500, 200), (5000, 100)]),
+                destination: pool
+            })
+        ));
+        assert_ok!(Assets::batch_transfer(
+            Origin::signed(ALICE),
+            asset_id,
+            vec![(BOB, 100), (ROOT, 500)]
+        ));
+        assert_eq!(Assets::free_balance(&asset_id, &pool), 25);
+        assert_ok!(Assets::transfer(Origin::signed(ALICE), asset_id, BOB, 5000));
+        assert_eq!(Assets::free_balance(&asset_id, &pool), 75);
+        assert_eq!(Assets::free_balance(&asset_id, &ALICE), 9490 - 610 - 5050);
+
+        assert_ok!(Assets::set_transfer_fee(Origin::ROOT, asset_id, None));
+        assert_ok!(Assets::transfer(Origin::signed(BOB), asset_id, ALICE, 100));
+        assert_eq!(Assets::free_balance(&asset_id, &pool), 75);
+    });
+}
+
+#[test]
+fn migration_hands_old_assets_to_module_account() {
+    with_externalities(&mut new_test_ext(), || {
+        // an asset created before owners, administrated by the old sudo key
+        let asset_id = Assets::get_current_asset_id();
+        let options = AssetOptions {
+            initial_issuance: 0,
+            permissions: PermissionLatest {
+                update: generic_asset::Owner::Address(ROOT),
+                mint: generic_asset::Owner::Address(ROOT),
+                burn: generic_asset::Owner::Address(ROOT),
+            },
+        };
+        assert_ok!(<generic_asset::Module<TestRuntime>>::create_asset(None, None, options));
+        <Symbols<TestRuntime>>::insert(asset_id, "OLD".as_bytes().to_vec());
+        StorageVersion::put(0);
+        assert!(Assets::make_mint(&asset_id, &ALICE, 100).is_err());
+
+        Assets::migrate_assets();
+        assert_eq!(Assets::storage_version(), STORAGE_VERSION);
+        assert_eq!(Assets::owner(&asset_id), Some(Assets::account_id()));
+        assert_eq!(Assets::asset_id_of_symbol("OLD".as_bytes().to_vec()), Some(asset_id));
+        assert_ok!(Assets::make_mint(&asset_id, &ALICE, 100));
+        assert_ok!(Assets::make_burn(&asset_id, &ALICE, 40));
+        assert_eq!(Assets::free_balance(&asset_id, &ALICE), 60);
+    });
+}
+
+#[test]
+fn admin_creates_assets_owned_by_module_account() {
+    with_externalities(&mut new_test_ext(), || {
+        let asset_id = Assets::get_current_asset_id();
+        assert!(Assets::create(Origin::signed(ROOT), 0, "SBTC".as_bytes().to_vec()).is_err());
+        assert_ok!(Assets::create(Origin::ROOT, 0, "SBTC".as_bytes().to_vec()));
+        assert_eq!(Assets::owner(&asset_id), Some(Assets::account_id()));
+
+        assert!(Assets::mint(Origin::signed(ROOT), asset_id, ALICE, 100).is_err());
+        assert_ok!(Assets::mint(Origin::ROOT, asset_id, ALICE, 100));
+        // other modules mint and burn without the admin origin
+        assert_ok!(Assets::make_burn(&asset_id, &ALICE, 40));
+        assert_ok!(Assets::make_mint(&asset_id, &BOB, 10));
+        assert_eq!(Assets::free_balance(&asset_id, &ALICE), 60);
+        assert_eq!(Assets::total_issuance(&asset_id), 70);
+    });
+}
@@ -0,0 +1,267 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+/// This module bridges an external chain asset (e.g. BTC as sBTC) into the assets module.
+/// Relayers watch the external chain and attest deposits made to the bridge, the bridged asset
+/// is minted to the depositor once "Threshold" relayers have attested the same deposit.
+/// The other way around, a withdrawal burns the bridged asset and records a request with the
+/// external destination address, which is fulfilled once "Threshold" relayers have attested
+/// the same external transaction paying it.
+/// An external output can only be minted once, and an external transaction can only fulfil one withdrawal.
+/// Until then, a relayer can correct its attestation by attesting again.
+/// A withdrawal that can't be paid, e.g. to an invalid destination, is rejected by relayers and
+/// refunded once "Threshold" relayers have rejected it. Nobody else can take a withdrawal back,
+/// as relayers may already be paying it on the external chain.
+use codec::{Decode, Encode};
+use rstd::prelude::*;
+use sp_runtime::traits::Zero;
+use support::{
+    decl_event, decl_module, decl_storage, dispatch::Result as DispatchResult, ensure,
+    traits::EnsureOrigin, weights::SimpleDispatchInfo,
+};
+use system::ensure_signed;
+
+mod mock;
+mod tests;
+
+/// the hash of an external transaction, e.g. a BTC txid
+pub type TxId = [u8; 32];
+pub type WithdrawalId = u64;
+
+/// the longest external destination address accepted
+pub const MAX_DESTINATION_LEN: usize = 128;
+
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Deposit<AccountId, Balance> {
+    /// (depositor, amount, relayer attesting it)
+    pub attestations: Vec<(AccountId, Balance, AccountId)>,
+    /// the depositor and the amount minted
+    pub minted: Option<(AccountId, Balance)>,
+}
+
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Withdrawal<AccountId, Balance> {
+    pub id: WithdrawalId,
+    pub who: AccountId,
+    pub amount: Balance,
+    pub destination: Vec<u8>,
+    /// (external tx id, relayers attesting it)
+    pub attestations: Vec<(TxId, AccountId)>,
+    /// the external tx that paid the withdrawal
+    pub fulfilled_by: Option<TxId>,
+    /// relayers attesting the withdrawal can't be paid
+    pub rejections: Vec<AccountId>,
+}
+
+pub trait Trait: assets::Trait {
+    type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+    /// the origin that manages the relayers, e.g. a collective, a multisig or sudo
+    type AdminOrigin: EnsureOrigin<Self::Origin>;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as Bridge {
+        /// the asset minted for external deposits and burnt for withdrawals
+        pub BridgedAssetId get(bridged_asset_id) config() : T::AssetId;
+        pub Relayers get(relayers) config() : Vec<T::AccountId>;
+        /// how many relayers must attest the same deposit or withdrawal
+        pub Threshold get(threshold) config() : u32;
+
+        /// (external tx id, output index) => deposit, kept after minted so it can't be replayed
+        pub Deposits get(deposit) : map (TxId, u32) => Option<Deposit<T::AccountId, T::Balance>>;
+
+        NextWithdrawalId get(next_withdrawal_id) : WithdrawalId;
+        pub Withdrawals get(withdrawal) : map WithdrawalId => Option<Withdrawal<T::AccountId, T::Balance>>;
+        /// external tx id => the withdrawal it fulfilled
+        pub FulfillingTxs get(fulfilled_withdrawal) : map TxId => Option<WithdrawalId>;
+    }
+
+    add_extra_genesis {
+        build(|config: &GenesisConfig<T>| {
+            assert!(config.threshold > 0, "zero is not allowed");
+            assert!(
+                config.threshold as usize <= config.relayers.len(),
+                "threshold is higher than the number of relayers"
+            );
+        });
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        fn deposit_event() = default;
+
+        /// replace the relayers and the threshold, attestations of removed relayers don't count anymore
+        /// *admin only*
+        #[weight = SimpleDispatchInfo::FreeOperational]
+        pub fn set_relayers(origin, relayers: Vec<T::AccountId>, threshold: u32) -> DispatchResult {
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
+            ensure!(threshold > 0, "zero is not allowed");
+            ensure!(threshold as usize <= relayers.len(), "threshold is higher than the number of relayers");
+            <Relayers<T>>::put(relayers);
+            Threshold::put(threshold);
+            Self::deposit_event(RawEvent::RelayersChanged(threshold));
+            Ok(())
+        }
+
+        /// a relayer attests that output "vout" of external tx "txid" deposits "amount" for "who"
+        /// the bridged asset is minted to "who" once the threshold of relayers attest the same "who" and "amount"
+        /// attesting again replaces the relayer's previous attestation of the deposit
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn attest_deposit(origin, txid: TxId, vout: u32, who: T::AccountId, amount: T::Balance) -> DispatchResult {
+            let relayer = Self::ensure_relayer(origin)?;
+            ensure!(!amount.is_zero(), "zero is not allowed");
+
+            let key = (txid, vout);
+            let mut deposit = Self::deposit(&key).unwrap_or_default();
+            ensure!(deposit.minted.is_none(), "deposit is already minted");
+            ensure!(
+                !deposit.attestations.contains(&(who.clone(), amount, relayer.clone())),
+                "deposit is already attested by the relayer"
+            );
+            deposit.attestations.retain(|(_, _, r)| *r != relayer);
+            deposit.attestations.push((who.clone(), amount, relayer.clone()));
+
+            let attested = deposit.attestations.iter()
+                .filter(|(w, a, _)| *w == who && *a == amount)
+                .map(|(_, _, r)| r);
+            let minted = Self::count_relayers(attested) >= Self::threshold();
+            if minted {
+                <assets::Module<T>>::make_mint(&Self::bridged_asset_id(), &who, amount)?;
+                deposit.minted = Some((who.clone(), amount));
+            }
+            <Deposits<T>>::insert(&key, deposit);
+            Self::deposit_event(RawEvent::DepositAttested(txid, vout, relayer));
+            if minted {
+                Self::deposit_event(RawEvent::Deposited(txid, vout, who, amount));
+            }
+            Ok(())
+        }
+
+        /// burn the bridged asset and request relayers to pay "amount" to "destination" on the external chain
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn withdraw(origin, amount: T::Balance, destination: Vec<u8>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(!amount.is_zero(), "zero is not allowed");
+            ensure!(!destination.is_empty() && destination.len() <= MAX_DESTINATION_LEN, "invalid destination");
+
+            <assets::Module<T>>::ensure_not_frozen(&Self::bridged_asset_id(), &who)?;
+            <assets::Module<T>>::make_burn(&Self::bridged_asset_id(), &who, amount)?;
+            let id = Self::next_withdrawal_id();
+            NextWithdrawalId::put(id + 1);
+            <Withdrawals<T>>::insert(id, Withdrawal {
+                id,
+                who: who.clone(),
+                amount,
+                destination: destination.clone(),
+                attestations: Vec::new(),
+                fulfilled_by: None,
+                rejections: Vec::new(),
+            });
+            Self::deposit_event(RawEvent::WithdrawalRequested(id, who, amount, destination));
+            Ok(())
+        }
+
+        /// a relayer attests that the withdrawal can't be paid, e.g. to an invalid destination
+        /// the bridged asset is minted back to the requester once the threshold of relayers reject it
+        /// rejecting replaces the relayer's attestation of a tx paying the withdrawal, and the other way around
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn reject_withdrawal(origin, id: WithdrawalId) -> DispatchResult {
+            let relayer = Self::ensure_relayer(origin)?;
+            let mut withdrawal = Self::withdrawal(id).ok_or("withdrawal doesn't exist")?;
+            ensure!(withdrawal.fulfilled_by.is_none(), "withdrawal is already fulfilled");
+            ensure!(!withdrawal.rejections.contains(&relayer), "withdrawal is already rejected by the relayer");
+            withdrawal.attestations.retain(|(_, r)| *r != relayer);
+            withdrawal.rejections.push(relayer.clone());
+            Self::deposit_event(RawEvent::WithdrawalRejected(id, relayer));
+
+            if Self::count_relayers(withdrawal.rejections.iter()) >= Self::threshold() {
+                return Self::refund(withdrawal);
+            }
+            <Withdrawals<T>>::insert(id, withdrawal);
+            Ok(())
+        }
+
+        /// a relayer attests that external tx "txid" has paid the withdrawal
+        /// attesting again replaces the relayer's previous attestation of the withdrawal
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn attest_withdrawal(origin, id: WithdrawalId, txid: TxId) -> DispatchResult {
+            let relayer = Self::ensure_relayer(origin)?;
+            let mut withdrawal = Self::withdrawal(id).ok_or("withdrawal doesn't exist")?;
+            ensure!(withdrawal.fulfilled_by.is_none(), "withdrawal is already fulfilled");
+            ensure!(!FulfillingTxs::exists(&txid), "tx has fulfilled another withdrawal");
+            ensure!(
+                !withdrawal.attestations.contains(&(txid, relayer.clone())),
+                "withdrawal is already attested by the relayer"
+            );
+            withdrawal.attestations.retain(|(_, r)| *r != relayer);
+            withdrawal.rejections.retain(|r| *r != relayer);
+            withdrawal.attestations.push((txid, relayer.clone()));
+            Self::deposit_event(RawEvent::WithdrawalAttested(id, txid, relayer));
+
+            let attested = withdrawal.attestations.iter().filter(|(t, _)| *t == txid).map(|(_, r)| r);
+            if Self::count_relayers(attested) >= Self::threshold() {
+                withdrawal.fulfilled_by = Some(txid);
+                FulfillingTxs::insert(&txid, id);
+                Self::deposit_event(RawEvent::WithdrawalFulfilled(id, txid));
+            }
+            <Withdrawals<T>>::insert(id, withdrawal);
+            Ok(())
+        }
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as system::Trait>::AccountId,
+        Balance = <T as pallet_generic_asset::Trait>::Balance,
+    {
+        /// new threshold
+        RelayersChanged(u32),
+        /// (txid, vout, relayer)
+        DepositAttested(TxId, u32, AccountId),
+        /// (txid, vout, depositor, amount minted)
+        Deposited(TxId, u32, AccountId, Balance),
+        /// (withdrawal id, who, amount burnt, destination)
+        WithdrawalRequested(WithdrawalId, AccountId, Balance, Vec<u8>),
+        /// (withdrawal id, txid, relayer)
+        WithdrawalAttested(WithdrawalId, TxId, AccountId),
+        WithdrawalFulfilled(WithdrawalId, TxId),
+        /// (withdrawal id, relayer)
+        WithdrawalRejected(WithdrawalId, AccountId),
+        /// (withdrawal id, who, amount minted back)
+        WithdrawalRefunded(WithdrawalId, AccountId, Balance),
+    }
+);
+
+impl<T: Trait> Module<T> {
+    fn ensure_relayer(origin: T::Origin) -> Result<T::AccountId, &'static str> {
+        let who = ensure_signed(origin)?;
+        ensure!(Self::relayers().contains(&who), "relayer only");
+        Ok(who)
+    }
+
+    fn refund(withdrawal: Withdrawal<T::AccountId, T::Balance>) -> DispatchResult {
+        <assets::Module<T>>::make_mint(
+            &Self::bridged_asset_id(),
+            &withdrawal.who,
+            withdrawal.amount,
+        )?;
+        <Withdrawals<T>>::remove(withdrawal.id);
+        Self::deposit_event(RawEvent::WithdrawalRefunded(
+            withdrawal.id,
+            withdrawal.who,
+            withdrawal.amount,
+        ));
+        Ok(())
+    }
+
+    /// how many of the attesting accounts are current relayers
+    fn count_relayers<'a>(attestations: impl Iterator<Item = &'a T::AccountId>) -> u32 {
+        let relayers = Self::relayers();
+        attestations.filter(|r| relayers.contains(r)).count() as u32
+    }
+}
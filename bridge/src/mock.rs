@@ -0,0 +1,161 @@
+#![cfg(test)]
+
+use super::*;
+use primitives::{Blake2Hasher, H256};
+use support::{impl_outer_origin, parameter_types};
+// The testing primitives are very useful for avoiding having to work with signatures
+// or public keys. `u64` is used as the `AccountId` and no `Signature`s are required.
+use balances;
+use pallet_generic_asset as generic_asset;
+
+#[allow(unused_imports)]
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup, OnFinalize, OnInitialize},
+    Perbill,
+};
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct TestRuntime;
+
+impl_outer_origin! {
+    pub enum Origin for TestRuntime {}
+}
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const MaximumBlockWeight: u32 = 1024;
+    pub const MaximumBlockLength: u32 = 2 * 1024;
+    pub const AvailableBlockRatio: Perbill = Perbill::one();
+}
+impl system::Trait for TestRuntime {
+    type Origin = Origin;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Call = ();
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type WeightMultiplierUpdate = ();
+    type Event = ();
+    type BlockHashCount = BlockHashCount;
+    type MaximumBlockWeight = MaximumBlockWeight;
+    type MaximumBlockLength = MaximumBlockLength;
+    type AvailableBlockRatio = AvailableBlockRatio;
+    type Version = ();
+}
+parameter_types! {
+    pub const ExistentialDeposit: u64 = 0;
+    pub const TransferFee: u64 = 0;
+    pub const CreationFee: u64 = 0;
+    pub const TransactionBaseFee: u64 = 0;
+    pub const TransactionByteFee: u64 = 0;
+}
+impl balances::Trait for TestRuntime {
+    type Balance = u128;
+    type OnFreeBalanceZero = ();
+    type OnNewAccount = ();
+    type Event = ();
+    type TransactionPayment = ();
+    type TransferPayment = ();
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type TransferFee = TransferFee;
+    type CreationFee = CreationFee;
+    type TransactionBaseFee = TransactionBaseFee;
+    type TransactionByteFee = TransactionByteFee;
+    type WeightToFee = ();
+}
+impl generic_asset::Trait for TestRuntime {
+    type Event = ();
+    type Balance = u128;
+    type AssetId = u32;
+}
+parameter_types! {
+    pub const CreationDeposit: u128 = 0;
+    pub const CreationCooldown: u64 = 0;
+}
+impl assets::Trait for TestRuntime {
+    type Event = ();
+    type AdminOrigin = system::EnsureRoot<u64>;
+    type Currency = balances::Module<TestRuntime>;
+    type CreationDeposit = CreationDeposit;
+    type CreationCooldown = CreationCooldown;
+    type OnAssetMint = ();
+    type OnAssetCreate = ();
+    type OnAssetTransfer = ();
+    type OnAssetBurn = ();
+    type OnAssetReaped = ();
+    type BeforeAssetDestroy = ();
+    type BeforeAssetMint = ();
+    type BeforeAssetCreate = ();
+    type BeforeAssetTransfer = ();
+    type BeforeAssetBurn = ();
+}
+impl Trait for TestRuntime {
+    type Event = ();
+    type AdminOrigin = system::EnsureRoot<u64>;
+}
+
+pub type Assets = assets::Module<TestRuntime>;
+pub type Bridge = Module<TestRuntime>;
+
+pub const ALICE: u64 = 2;
+pub const BOB: u64 = 3;
+pub const RELAYER_1: u64 = 11;
+pub const RELAYER_2: u64 = 12;
+pub const RELAYER_3: u64 = 13;
+
+pub const SBTC_ASSET_ID: u32 = 1;
+
+/// stands for a relayer process watching the external chain
+pub struct MockRelayer(pub u64);
+
+impl MockRelayer {
+    pub fn saw_deposit(&self, txid: TxId, vout: u32, who: u64, amount: u128) -> DispatchResult {
+        Bridge::attest_deposit(Origin::signed(self.0), txid, vout, who, amount)
+    }
+
+    pub fn saw_withdrawal_paid(&self, id: WithdrawalId, txid: TxId) -> DispatchResult {
+        Bridge::attest_withdrawal(Origin::signed(self.0), id, txid)
+    }
+
+    pub fn saw_withdrawal_unpayable(&self, id: WithdrawalId) -> DispatchResult {
+        Bridge::reject_withdrawal(Origin::signed(self.0), id)
+    }
+}
+
+pub fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+    let mut t = system::GenesisConfig::default()
+        .build_storage::<TestRuntime>()
+        .unwrap();
+
+    generic_asset::GenesisConfig::<TestRuntime> {
+        next_asset_id: 9,
+        staking_asset_id: 0,
+        spending_asset_id: 0,
+        assets: vec![],
+        initial_balance: 0,
+        endowed_accounts: vec![],
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+
+    assets::GenesisConfig::<TestRuntime> {
+        symbols: vec![(SBTC_ASSET_ID, "SBTC".as_bytes().to_vec())],
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+
+    GenesisConfig::<TestRuntime> {
+        bridged_asset_id: SBTC_ASSET_ID,
+        relayers: vec![RELAYER_1, RELAYER_2, RELAYER_3],
+        threshold: 2,
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+
+    t.into()
+}
@@ -0,0 +1,181 @@
+#![cfg(test)]
+
+use super::*;
+
+use runtime_io::with_externalities;
+use support::{assert_noop, assert_ok};
+
+use mock::*;
+
+#[test]
+fn deposit_is_minted_at_threshold() {
+    with_externalities(&mut new_test_ext(), || {
+        let txid = [1u8; 32];
+        assert_noop!(
+            Bridge::attest_deposit(Origin::signed(ALICE), txid, 0, ALICE, 100),
+            "relayer only"
+        );
+
+        assert_ok!(MockRelayer(RELAYER_1).saw_deposit(txid, 0, ALICE, 100));
+        assert_eq!(Assets::free_balance(&SBTC_ASSET_ID, &ALICE), 0);
+        assert_noop!(
+            MockRelayer(RELAYER_1).saw_deposit(txid, 0, ALICE, 100),
+            "deposit is already attested by the relayer"
+        );
+        // attestations of a different depositor or amount don't add up
+        assert_ok!(MockRelayer(RELAYER_2).saw_deposit(txid, 0, ALICE, 101));
+        assert_ok!(MockRelayer(RELAYER_3).saw_deposit(txid, 0, BOB, 100));
+        assert_eq!(Assets::free_balance(&SBTC_ASSET_ID, &ALICE), 0);
+        assert_eq!(Assets::free_balance(&SBTC_ASSET_ID, &BOB), 0);
+
+        // a relayer corrects its attestation
+        assert_ok!(MockRelayer(RELAYER_2).saw_deposit(txid, 0, ALICE, 100));
+        assert_eq!(Assets::free_balance(&SBTC_ASSET_ID, &ALICE), 100);
+        assert_eq!(
+            Bridge::deposit((txid, 0)).unwrap().minted,
+            Some((ALICE, 100))
+        );
+        // the same output can't be minted twice
+        assert_noop!(
+            MockRelayer(RELAYER_3).saw_deposit(txid, 0, ALICE, 100),
+            "deposit is already minted"
+        );
+        // another output of the same tx is a different deposit
+        assert_ok!(MockRelayer(RELAYER_2).saw_deposit(txid, 1, BOB, 50));
+        assert_ok!(MockRelayer(RELAYER_3).saw_deposit(txid, 1, BOB, 50));
+        assert_eq!(Assets::free_balance(&SBTC_ASSET_ID, &BOB), 50);
+    });
+}
+
+#[test]
+fn removed_relayers_do_not_count() {
+    with_externalities(&mut new_test_ext(), || {
+        let txid = [2u8; 32];
+        assert_ok!(MockRelayer(RELAYER_1).saw_deposit(txid, 0, ALICE, 100));
+        assert_noop!(
+            Bridge::set_relayers(Origin::ROOT, vec![RELAYER_2], 2),
+            "threshold is higher than the number of relayers"
+        );
+        assert_ok!(Bridge::set_relayers(
+            Origin::ROOT,
+            vec![RELAYER_2, RELAYER_3],
+            2
+        ));
+
+        assert_ok!(MockRelayer(RELAYER_2).saw_deposit(txid, 0, ALICE, 100));
+        assert_eq!(Assets::free_balance(&SBTC_ASSET_ID, &ALICE), 0);
+        assert_ok!(MockRelayer(RELAYER_3).saw_deposit(txid, 0, ALICE, 100));
+        assert_eq!(Assets::free_balance(&SBTC_ASSET_ID, &ALICE), 100);
+    });
+}
+
+#[test]
+fn withdrawal_is_fulfilled_at_threshold() {
+    with_externalities(&mut new_test_ext(), || {
+        assert_ok!(MockRelayer(RELAYER_1).saw_deposit([1u8; 32], 0, ALICE, 100));
+        assert_ok!(MockRelayer(RELAYER_2).saw_deposit([1u8; 32], 0, ALICE, 100));
+
+        let destination = "bc1qdefinex".as_bytes().to_vec();
+        assert_noop!(
+            Bridge::withdraw(Origin::signed(ALICE), 100, vec![]),
+            "invalid destination"
+        );
+        assert_ok!(Bridge::withdraw(
+            Origin::signed(ALICE),
+            60,
+            destination.clone()
+        ));
+        assert_ok!(Bridge::withdraw(
+            Origin::signed(ALICE),
+            40,
+            destination.clone()
+        ));
+        assert_eq!(Assets::free_balance(&SBTC_ASSET_ID, &ALICE), 0);
+        assert_eq!(Assets::total_issuance(&SBTC_ASSET_ID), 0);
+        assert_eq!(Bridge::withdrawal(0).unwrap().destination, destination);
+
+        let paid = [3u8; 32];
+        // attestations of different txs don't add up
+        assert_ok!(MockRelayer(RELAYER_1).saw_withdrawal_paid(0, paid));
+        assert_ok!(MockRelayer(RELAYER_2).saw_withdrawal_paid(0, [4u8; 32]));
+        assert_eq!(Bridge::withdrawal(0).unwrap().fulfilled_by, None);
+        assert_ok!(MockRelayer(RELAYER_3).saw_withdrawal_paid(0, paid));
+        assert_eq!(Bridge::withdrawal(0).unwrap().fulfilled_by, Some(paid));
+
+        assert_noop!(
+            MockRelayer(RELAYER_2).saw_withdrawal_paid(0, paid),
+            "withdrawal is already fulfilled"
+        );
+        // a tx can't fulfil another withdrawal
+        assert_noop!(
+            MockRelayer(RELAYER_1).saw_withdrawal_paid(1, paid),
+            "tx has fulfilled another withdrawal"
+        );
+    });
+}
+
+#[test]
+fn unpayable_withdrawal_is_refunded_at_threshold() {
+    with_externalities(&mut new_test_ext(), || {
+        assert_ok!(MockRelayer(RELAYER_1).saw_deposit([1u8; 32], 0, ALICE, 100));
+        assert_ok!(MockRelayer(RELAYER_2).saw_deposit([1u8; 32], 0, ALICE, 100));
+        let destination = "bc1qdefinex".as_bytes().to_vec();
+
+        // a frozen account can't bridge out
+        assert_ok!(Assets::freeze(Origin::ROOT, SBTC_ASSET_ID, ALICE));
+        assert_noop!(
+            Bridge::withdraw(Origin::signed(ALICE), 60, destination.clone()),
+            "account is frozen for this asset"
+        );
+        assert_ok!(Assets::thaw(Origin::ROOT, SBTC_ASSET_ID, ALICE));
+
+        assert_ok!(Bridge::withdraw(
+            Origin::signed(ALICE),
+            60,
+            destination.clone()
+        ));
+        assert_ok!(Bridge::withdraw(
+            Origin::signed(ALICE),
+            40,
+            destination.clone()
+        ));
+        assert_noop!(
+            Bridge::reject_withdrawal(Origin::signed(ALICE), 0),
+            "relayer only"
+        );
+
+        // a relayer that attested a paying tx changes its mind
+        assert_ok!(MockRelayer(RELAYER_1).saw_withdrawal_paid(0, [3u8; 32]));
+        assert_ok!(MockRelayer(RELAYER_1).saw_withdrawal_unpayable(0));
+        assert!(Bridge::withdrawal(0).unwrap().attestations.is_empty());
+        assert_noop!(
+            MockRelayer(RELAYER_1).saw_withdrawal_unpayable(0),
+            "withdrawal is already rejected by the relayer"
+        );
+        assert_eq!(Assets::free_balance(&SBTC_ASSET_ID, &ALICE), 0);
+        assert_ok!(MockRelayer(RELAYER_2).saw_withdrawal_unpayable(0));
+        assert_eq!(Assets::free_balance(&SBTC_ASSET_ID, &ALICE), 60);
+        assert_eq!(Bridge::withdrawal(0), None);
+
+        // a paid withdrawal can't be rejected
+        assert_ok!(MockRelayer(RELAYER_1).saw_withdrawal_paid(1, [4u8; 32]));
+        assert_ok!(MockRelayer(RELAYER_2).saw_withdrawal_paid(1, [4u8; 32]));
+        assert_noop!(
+            MockRelayer(RELAYER_3).saw_withdrawal_unpayable(1),
+            "withdrawal is already fulfilled"
+        );
+        assert_eq!(Assets::free_balance(&SBTC_ASSET_ID, &ALICE), 60);
+    });
+}
+
+#[test]
+#[should_panic(expected = "zero is not allowed")]
+fn zero_threshold_is_rejected_at_genesis() {
+    GenesisConfig::<TestRuntime> {
+        bridged_asset_id: SBTC_ASSET_ID,
+        relayers: vec![RELAYER_1],
+        threshold: 0,
+    }
+    .build_storage()
+    .unwrap();
+}
@@ -0,0 +1,168 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+/// This module gates who can hold and move an asset.
+/// Each asset is either open to everyone, restricted to an allowlist (e.g. KYC'd accounts),
+/// or open to everyone but a denylist.
+/// It plugs into the assets module as T::BeforeAssetTransfer, T::BeforeAssetMint & T::BeforeAssetBurn,
+/// so it gates the transfers of other modules (e.g. loan, saving and the AMM) and the transfer fee destination too.
+use codec::{Decode, Encode};
+use support::{
+    decl_event, decl_module, decl_storage, dispatch::Result as DispatchResult, ensure,
+    weights::SimpleDispatchInfo,
+};
+
+mod mock;
+mod tests;
+
+#[derive(Encode, Decode, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum ComplianceMode {
+    Open,
+    /// only allowlisted accounts can receive, send or burn the asset
+    Allowlist,
+    /// denylisted accounts can't receive, send or burn the asset
+    Denylist,
+}
+impl Default for ComplianceMode {
+    fn default() -> Self {
+        ComplianceMode::Open
+    }
+}
+
+pub trait Trait: assets::Trait {
+    type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as Compliance {
+        pub Modes get(mode) : map T::AssetId => ComplianceMode;
+        /// both lists are kept when the mode changes, only the one of the current mode is checked
+        pub Allowlisted get(is_allowlisted) : map (T::AssetId, T::AccountId) => bool;
+        pub Denylisted get(is_denylisted) : map (T::AssetId, T::AccountId) => bool;
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        fn deposit_event() = default;
+
+        /// *asset owner or admin only*
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn set_mode(origin, #[compact] asset_id: T::AssetId, mode: ComplianceMode) -> DispatchResult {
+            <assets::Module<T>>::ensure_owner_or_admin(origin, &asset_id)?;
+            <Modes<T>>::insert(&asset_id, mode);
+            Self::deposit_event(RawEvent::ModeChanged(asset_id, mode));
+            Ok(())
+        }
+
+        /// *asset owner or admin only*
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn allow(origin, #[compact] asset_id: T::AssetId, who: T::AccountId) -> DispatchResult {
+            <assets::Module<T>>::ensure_owner_or_admin(origin, &asset_id)?;
+            ensure!(!Self::is_allowlisted((asset_id, who.clone())), "account is already allowlisted");
+            <Allowlisted<T>>::insert((asset_id, who.clone()), true);
+            Self::deposit_event(RawEvent::Allowlisted(asset_id, who));
+            Ok(())
+        }
+
+        /// *asset owner or admin only*
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn disallow(origin, #[compact] asset_id: T::AssetId, who: T::AccountId) -> DispatchResult {
+            <assets::Module<T>>::ensure_owner_or_admin(origin, &asset_id)?;
+            ensure!(Self::is_allowlisted((asset_id, who.clone())), "account is not allowlisted");
+            <Allowlisted<T>>::remove((asset_id, who.clone()));
+            Self::deposit_event(RawEvent::Disallowed(asset_id, who));
+            Ok(())
+        }
+
+        /// *asset owner or admin only*
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn deny(origin, #[compact] asset_id: T::AssetId, who: T::AccountId) -> DispatchResult {
+            <assets::Module<T>>::ensure_owner_or_admin(origin, &asset_id)?;
+            ensure!(!Self::is_denylisted((asset_id, who.clone())), "account is already denylisted");
+            <Denylisted<T>>::insert((asset_id, who.clone()), true);
+            Self::deposit_event(RawEvent::Denylisted(asset_id, who));
+            Ok(())
+        }
+
+        /// *asset owner or admin only*
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn undeny(origin, #[compact] asset_id: T::AssetId, who: T::AccountId) -> DispatchResult {
+            <assets::Module<T>>::ensure_owner_or_admin(origin, &asset_id)?;
+            ensure!(Self::is_denylisted((asset_id, who.clone())), "account is not denylisted");
+            <Denylisted<T>>::remove((asset_id, who.clone()));
+            Self::deposit_event(RawEvent::Undenied(asset_id, who));
+            Ok(())
+        }
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as system::Trait>::AccountId,
+        AssetId = <T as pallet_generic_asset::Trait>::AssetId,
+    {
+        ModeChanged(AssetId, ComplianceMode),
+        Allowlisted(AssetId, AccountId),
+        Disallowed(AssetId, AccountId),
+        Denylisted(AssetId, AccountId),
+        Undenied(AssetId, AccountId),
+    }
+);
+
+impl<T: Trait> Module<T> {
+    /// whether "who" may hold and move the asset under its current mode
+    pub fn ensure_compliant(asset_id: &T::AssetId, who: &T::AccountId) -> DispatchResult {
+        match Self::mode(asset_id) {
+            ComplianceMode::Open => {}
+            ComplianceMode::Allowlist => ensure!(
+                Self::is_allowlisted((*asset_id, who.clone())),
+                "account is not allowlisted for this asset"
+            ),
+            ComplianceMode::Denylist => ensure!(
+                !Self::is_denylisted((*asset_id, who.clone())),
+                "account is denylisted for this asset"
+            ),
+        }
+        Ok(())
+    }
+}
+
+impl<T: Trait> assets::traits::BeforeAssetTransfer<T::AssetId, T::AccountId, T::Balance>
+    for Module<T>
+{
+    fn before_asset_transfer(
+        asset_id: &T::AssetId,
+        from: &T::AccountId,
+        to: &T::AccountId,
+        _balance: &T::Balance,
+    ) -> DispatchResult {
+        Self::ensure_compliant(asset_id, from)?;
+        Self::ensure_compliant(asset_id, to)
+    }
+}
+
+impl<T: Trait> assets::traits::BeforeAssetMint<T::AssetId, T::AccountId, T::Balance>
+    for Module<T>
+{
+    fn before_asset_mint(
+        asset_id: &T::AssetId,
+        to: &T::AccountId,
+        _balance: &T::Balance,
+    ) -> DispatchResult {
+        Self::ensure_compliant(asset_id, to)
+    }
+}
+
+impl<T: Trait> assets::traits::BeforeAssetBurn<T::AssetId, T::AccountId, T::Balance>
+    for Module<T>
+{
+    fn before_asset_burn(
+        asset_id: &T::AssetId,
+        to: &T::AccountId,
+        _balance: &T::Balance,
+    ) -> DispatchResult {
+        Self::ensure_compliant(asset_id, to)
+    }
+}
@@ -0,0 +1,104 @@
+#![cfg(test)]
+
+use super::*;
+
+use runtime_io::with_externalities;
+use support::{assert_noop, assert_ok};
+
+use mock::*;
+
+#[test]
+fn allowlist_works() {
+    with_externalities(&mut new_test_ext(), || {
+        let asset_id = Assets::get_current_asset_id();
+        assert_ok!(Assets::create(Origin::ROOT, 0, "KYC".as_bytes().to_vec()));
+        assert_ok!(Assets::mint(Origin::ROOT, asset_id, ALICE, 100));
+
+        assert_noop!(
+            Compliance::set_mode(Origin::signed(ALICE), asset_id, ComplianceMode::Allowlist),
+            "asset owner only"
+        );
+        assert_ok!(Compliance::set_mode(Origin::ROOT, asset_id, ComplianceMode::Allowlist));
+        assert_ok!(Compliance::allow(Origin::ROOT, asset_id, ALICE));
+
+        assert_noop!(
+            Assets::transfer(Origin::signed(ALICE), asset_id, BOB, 10),
+            "account is not allowlisted for this asset"
+        );
+        assert_noop!(
+            Assets::mint(Origin::ROOT, asset_id, BOB, 10),
+            "account is not allowlisted for this asset"
+        );
+
+        assert_ok!(Compliance::allow(Origin::ROOT, asset_id, BOB));
+        assert_ok!(Assets::transfer(Origin::signed(ALICE), asset_id, BOB, 10));
+        assert_ok!(Assets::mint(Origin::ROOT, asset_id, BOB, 10));
+
+        assert_ok!(Compliance::disallow(Origin::ROOT, asset_id, BOB));
+        assert_noop!(
+            Assets::burn(Origin::ROOT, asset_id, BOB, 10),
+            "account is not allowlisted for this asset"
+        );
+        assert_eq!(Assets::free_balance(&asset_id, &BOB), 20);
+    });
+}
+
+#[test]
+fn denylist_works() {
+    with_externalities(&mut new_test_ext(), || {
+        let asset_id = Assets::get_current_asset_id();
+        assert_ok!(Assets::create(Origin::ROOT, 0, "SBTC".as_bytes().to_vec()));
+        assert_ok!(Assets::mint(Origin::ROOT, asset_id, ALICE, 100));
+
+        assert_ok!(Compliance::deny(Origin::ROOT, asset_id, CHRIS));
+        // lists only apply in their mode
+        assert_ok!(Assets::transfer(Origin::signed(ALICE), asset_id, CHRIS, 10));
+
+        assert_ok!(Compliance::set_mode(Origin::ROOT, asset_id, ComplianceMode::Denylist));
+        assert_noop!(
+            Assets::transfer(Origin::signed(CHRIS), asset_id, BOB, 10),
+            "account is denylisted for this asset"
+        );
+        assert_ok!(Assets::transfer(Origin::signed(ALICE), asset_id, BOB, 10));
+
+        assert_ok!(Compliance::undeny(Origin::ROOT, asset_id, CHRIS));
+        assert_ok!(Assets::transfer(Origin::signed(CHRIS), asset_id, BOB, 10));
+        assert_eq!(Assets::free_balance(&asset_id, &BOB), 20);
+    });
+}
+
+#[test]
+fn module_transfers_and_fees_are_gated() {
+    with_externalities(&mut new_test_ext(), || {
+        let asset_id = Assets::get_current_asset_id();
+        assert_ok!(Assets::create(Origin::ROOT, 0, "KYC".as_bytes().to_vec()));
+        assert_ok!(Assets::mint(Origin::ROOT, asset_id, ALICE, 100));
+        assert_ok!(Compliance::set_mode(Origin::ROOT, asset_id, ComplianceMode::Allowlist));
+        assert_ok!(Compliance::allow(Origin::ROOT, asset_id, ALICE));
+        assert_ok!(Compliance::allow(Origin::ROOT, asset_id, BOB));
+
+        // e.g. a loan paying out or an AMM swap
+        assert_noop!(
+            Assets::make_transfer_with_event(&asset_id, &ALICE, &CHRIS, 10),
+            "account is not allowlisted for this asset"
+        );
+        assert_ok!(Assets::make_transfer_with_event(&asset_id, &ALICE, &BOB, 10));
+
+        assert_ok!(Assets::set_transfer_fee(
+            Origin::ROOT,
+            asset_id,
+            Some(assets::TransferFee {
+                kind: assets::FeeKind::Flat(1),
+                destination: CHRIS,
+            })
+        ));
+        assert_noop!(
+            Assets::transfer(Origin::signed(ALICE), asset_id, BOB, 10),
+            "account is not allowlisted for this asset"
+        );
+        assert_ok!(Compliance::allow(Origin::ROOT, asset_id, CHRIS));
+        assert_ok!(Assets::transfer(Origin::signed(ALICE), asset_id, BOB, 10));
+        assert_eq!(Assets::free_balance(&asset_id, &BOB), 20);
+        assert_eq!(Assets::free_balance(&asset_id, &CHRIS), 1);
+    });
+}
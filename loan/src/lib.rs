@@ -16,16 +16,20 @@ use support::{
     decl_event, decl_module, decl_storage,
     dispatch::{Parameter, Result as DispatchResult},
     ensure,
-    traits::{Contains, Get},
+    traits::{Contains, EnsureOrigin, Get},
     weights::SimpleDispatchInfo,
 };
 #[allow(unused_imports)]
-use system::{ensure_root, ensure_signed, Error};
+use system::{ensure_signed, Error};
 
 #[allow(unused_imports)]
-use sp_runtime::traits::{
-    Bounded, CheckedAdd, CheckedMul, CheckedSub, MaybeDisplay, MaybeSerializeDeserialize, Member,
-    One, Saturating, SignedExtension, SimpleArithmetic, Zero,
+use sp_runtime::{
+    traits::{
+        AccountIdConversion, Bounded, CheckedAdd, CheckedMul, CheckedSub, Dispatchable,
+        MaybeDisplay, MaybeSerializeDeserialize, Member, One, Saturating, SignedExtension,
+        SimpleArithmetic, Zero,
+    },
+    ModuleId,
 };
 // use sp_runtime::{
 // 	  transaction_validity::{
@@ -49,6 +53,15 @@ pub const TERMS_UNIT: u32 = 86400;
 /// in terms of TERMS_UNIT, a.k.a 2 days
 pub const DUE_EXTEND: u32 = 2;
 
+/// the most discount on the oracle price a self liquidation can sell collateral at, in LTV_PREC
+pub const MAX_SELF_LIQUIDATION_DISCOUNT: u32 = 1000;
+
+/// the module account holds the rebatable share of the prepaid interest until the loan is closed
+pub const MODULE_ID: ModuleId = ModuleId(*b"dfx/loan");
+
+/// version 1 stores the prepaid interest of the current term in "Loan"
+pub const STORAGE_VERSION: u32 = 1;
+
 pub type LoanPackageId = u64;
 pub type LoanId = u64;
 pub type CreditLineId = u64;
@@ -139,6 +152,26 @@ where
 #[derive(Encode, Decode, Clone, Default, PartialEq, Eq)]
 #[cfg_attr(feature = "std", derive(Debug))]
 pub struct Loan<AccountId, Balance, Moment> {
+    pub id: LoanId,
+    pub package_id: LoanPackageId,
+    /// the holder of the loan's token
+    pub who: AccountId,
+    pub due: Moment,
+    pub due_extend: Moment,
+    /// when the current term started, it ends at "due"
+    pub term_start: Moment,
+    pub collateral_balance_original: Balance,
+    pub collateral_balance_available: Balance,
+    pub loan_balance_total: Balance,
+    /// the interest prepaid in TBD for the current term, including the interest of draws during it
+    pub interest_prepaid: Balance,
+    /// the part of "interest_prepaid" held by the module account for an early repayment rebate
+    pub interest_reserved: Balance,
+    pub status: LoanHealth,
+}
+/// "Loan" as stored before version 1 of the storage, without the prepaid interest of the current term
+#[derive(Encode, Decode)]
+pub struct LoanV0<AccountId, Balance, Moment> {
     pub id: LoanId,
     pub package_id: LoanPackageId,
     pub who: AccountId,
@@ -149,6 +182,29 @@ pub struct Loan<AccountId, Balance, Moment> {
     pub loan_balance_total: Balance,
     pub status: LoanHealth,
 }
+
+impl<AccountId, Balance: Zero, Moment: Copy> From<LoanV0<AccountId, Balance, Moment>>
+    for Loan<AccountId, Balance, Moment>
+{
+    /// nothing was recorded as prepaid, so no rebate is owed for the current term
+    fn from(old: LoanV0<AccountId, Balance, Moment>) -> Self {
+        Loan {
+            id: old.id,
+            package_id: old.package_id,
+            who: old.who,
+            due: old.due,
+            due_extend: old.due_extend,
+            term_start: old.due,
+            collateral_balance_original: old.collateral_balance_original,
+            collateral_balance_available: old.collateral_balance_available,
+            loan_balance_total: old.loan_balance_total,
+            interest_prepaid: Zero::zero(),
+            interest_reserved: Zero::zero(),
+            status: old.status,
+        }
+    }
+}
+
 impl<AccountId, Balance, Moment> Loan<AccountId, Balance, Moment>
 where
     Balance: Encode
@@ -171,6 +227,22 @@ where
         TryInto::<LTV>::try_into(ltv).ok().unwrap()
     }
 
+    /// the share of the prepaid interest for the time left in the current term
+    pub fn unused_interest(&self, now: Moment) -> Balance {
+        if now >= self.due || self.term_start >= self.due {
+            return Balance::zero();
+        }
+        let term = TryInto::<u64>::try_into(self.due - self.term_start)
+            .ok()
+            .unwrap();
+        let unused = TryInto::<u64>::try_into(self.due - now)
+            .ok()
+            .unwrap()
+            .min(term);
+        self.interest_prepaid * <Balance as TryFrom<u64>>::try_from(unused).ok().unwrap()
+            / <Balance as TryFrom<u64>>::try_from(term).ok().unwrap()
+    }
+
     pub fn expiration_penalty(&self, penalty_rate: u32) -> Balance {
         self.collateral_balance_available * Balance::from(penalty_rate) / Balance::from(LTV_PREC)
     }
@@ -213,9 +285,26 @@ where
         let (due, due_extend) = package.get_dues(now);
         self.due = due;
         self.due_extend = due_extend;
+        // the interest of the new term is paid in collateral
+        self.term_start = now;
+        self.interest_prepaid = Balance::zero();
+        self.interest_reserved = Balance::zero();
     }
 }
 
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct LiquidationRecord<AccountId, Moment, BlockNumber> {
+    pub started: Moment,
+    /// the block the liquidation escalates at if the loan is not marked liquidated by then,
+    /// None while there's no "LiquidationTimeout"
+    pub deadline: Option<BlockNumber>,
+    /// the account expected to sell the collateral and call "mark_liquidated"
+    pub liquidator: AccountId,
+    /// how many deadlines the liquidation has missed
+    pub escalations: u32,
+}
+
 #[derive(Encode, Decode, Clone, Default, PartialEq, Eq)]
 #[cfg_attr(feature = "std", derive(Debug))]
 pub struct CreditLine<Balance, AssetId> {
@@ -228,9 +317,15 @@ pub struct CreditLine<Balance, AssetId> {
 }
 
 /// The module's configuration trait.
-pub trait Trait: assets::Trait + timestamp::Trait {
+pub trait Trait: assets::Trait + timestamp::Trait + nft::Trait {
     /// The overarching event type.
     type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+    /// the origin that configures the module, e.g. a collective, a multisig or sudo
+    type AdminOrigin: EnsureOrigin<Self::Origin>;
+
+    /// the call a flash mint dispatches while the minted TBD is in the caller's hands
+    type Callback: Parameter + Dispatchable<Origin = <Self as system::Trait>::Origin>;
 }
 
 // This module's storage items.
@@ -252,30 +347,50 @@ decl_storage! {
         pub GlobalWarningThreshold get(global_warning_threshold) config() : LTV;
         /// increase monotonically
         NextLoanPackageId get(next_loan_package_id) config() : LoanPackageId;
+        /// the layout version of the storage, the runtime upgrade migrates it to "STORAGE_VERSION"
+        StorageVersion get(storage_version) build(|_config: &GenesisConfig<T>| STORAGE_VERSION) : u32;
         /// currently active packages that users can make new loans with
         pub ActiveLoanPackages get(active_loan_packages) : linked_map LoanPackageId => LoanPackage<T::Balance, T::AssetId>;
         /// all packages including both the active and the inactive
         pub LoanPackages get(loan_package) : linked_map LoanPackageId => LoanPackage<T::Balance, T::AssetId>;
+        /// package id => the share of the unused prepaid interest rebated when a loan is repaid before due, in LTV_PREC
+        pub RebateRates get(rebate_rate) : map LoanPackageId => u32;
         /// increase monotonically
         NextLoanId get(next_loan_id) config() : LoanId;
         /// currently running loans
         pub Loans get(get_loan_by_id) : linked_map LoanId => Loan<T::AccountId, T::Balance, T::Moment>;
         /// loan id aggregated by account
         pub LoansByAccount get(loans_by_account) : map T::AccountId => Vec<LoanId>;
+        /// the nft class of loan positions, each loan is a token of it with the loan id as the token id
+        LoanNftClass get(loan_nft_class) : Option<nft::ClassId>;
+        /// loan id => the account the owner has offered the loan to
+        pub LoanTransferOffers get(loan_transfer_offer) : map LoanId => Option<T::AccountId>;
         /// current btc price coming from Price
         CurrentBTCPrice get(current_btc_price) config() : Price;
+        /// when "CurrentBTCPrice" was last set
+        pub PriceUpdatedAt get(price_updated_at) : T::Moment;
         /// total balance of loan asset in circulation
         TotalLoan get(total_loan) : T::Balance;
         /// total balance of collateral asset locked in the pawnshop
         TotalCollateral get(total_collateral) : T::Balance;
         /// total balance of profit that we have gained from fees and penaltys
         TotalProfit get(total_profit) : T::Balance;
+        /// total balance of penalties paid by liquidated loans, kept apart from "TotalProfit"
+        TotalLiquidationPenalty get(total_liquidation_penalty) : T::Balance;
         /// when a loan is overdue, a small portion of its collateral will be cut as penalty
         pub PenaltyRate get(penalty_rate) config() : u32;
         /// the official account take charge of selling the collateral asset of liquidating loans
         LiquidationAccount get(liquidation_account) config() : T::AccountId;
-        /// loans which are in liquidating, these loans will not be in "Loans" & "LoansByAccount"
-        pub LiquidatingLoans get(liquidating_loans) : Vec<LoanId>;
+        /// replaced by "LiquidationRecords", only read by the runtime upgrade which moves what's left there
+        LiquidatingLoans : Vec<LoanId>;
+        /// loan id => how the liquidation of a loan in liquidating is going, every loan in liquidating has one
+        pub LiquidationRecords get(liquidation_record) : linked_map LoanId => LiquidationRecord<T::AccountId, T::Moment, T::BlockNumber>;
+        /// block number => the liquidations whose deadline is at this block
+        LiquidationDeadlines get(liquidations_due_at) : map T::BlockNumber => Vec<LoanId>;
+        /// how many blocks a liquidator has to mark a loan liquidated before the liquidation escalates, no deadlines if zero
+        pub LiquidationTimeout get(liquidation_timeout) : T::BlockNumber;
+        /// the liquidator that liquidations missing their deadline are handed off to
+        pub BackupLiquidationAccount get(backup_liquidation_account) : Option<T::AccountId>;
         /// a global cap of loan balance, no caps at all if None
         pub LoanCap get(loan_cap) : Option<T::Balance>;
         /// module level switch
@@ -284,6 +399,29 @@ decl_storage! {
         pub MinimumCollateral get(minimum_collateral) config() : T::Balance;
         ///
         pub LiquidationPenalty get(liquidation_penalty) config() : u32;
+        /// the account that covers the shortfalls of liquidations, no insurance if None
+        /// it gets its share of the interest and penalties, those of expired loans in collateral
+        pub InsuranceFund get(insurance_fund) : Option<T::AccountId>;
+        /// the share of the interest and the liquidation penalties that goes to "InsuranceFund", in LTV_PREC
+        pub InsuranceRate get(insurance_rate) : u32;
+        /// loan id => the shortfall of its liquidation that "InsuranceFund" couldn't cover
+        pub BadDebts get(bad_debt_of) : map LoanId => T::Balance;
+        /// total balance of shortfalls that "InsuranceFund" couldn't cover, a.k.a the protocol debt
+        TotalBadDebt get(total_bad_debt) : T::Balance;
+        /// the account that buys the collateral of self liquidating loans with TBD, self liquidation is disabled if None
+        pub SelfLiquidationMarketMaker get(self_liquidation_market_maker) : Option<T::AccountId>;
+        /// the discount on the oracle price the market maker buys collateral at, in LTV_PREC
+        pub SelfLiquidationDiscount get(self_liquidation_discount) : u32;
+        /// the fee of a self liquidation, in LTV_PREC of the loan balance, lower than "LiquidationPenalty"
+        pub SelfLiquidationFee get(self_liquidation_fee) : u32;
+        /// self liquidations are refused on a price older than this
+        pub SelfLiquidationMaxPriceAge get(self_liquidation_max_price_age) : T::Moment;
+        /// the most TBD a single flash mint can mint, flash mints are disabled while it's zero
+        pub FlashMintCap get(flash_mint_cap) : T::Balance;
+        /// the fee of a flash mint, in LTV_PREC of the minted amount
+        pub FlashMintFeeRate get(flash_mint_fee_rate) : u32;
+        /// set during the callback of a flash mint, flash mints can't be nested
+        FlashMinting get(flash_minting) : bool;
     }
 }
 
@@ -300,64 +438,71 @@ decl_module! {
         fn on_initialize(height: T::BlockNumber) {
             if !Self::paused() {
                 Self::on_each_block(height);
+            } else {
+                Self::defer_liquidation_deadlines(height);
             }
         }
 
+        fn on_runtime_upgrade() {
+            Self::migrate_loans();
+            Self::migrate_liquidating_loans();
+        }
+
         fn on_finalize(_height: T::BlockNumber) {
         }
 
         #[weight = SimpleDispatchInfo::MaxNormal]
         pub fn pause(origin) -> DispatchResult {
-            ensure_root(origin)?;
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
             Paused::mutate(|v| *v = true);
             Ok(())
         }
 
         #[weight = SimpleDispatchInfo::MaxNormal]
         pub fn resume(origin) -> DispatchResult {
-            ensure_root(origin)?;
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
             Paused::mutate(|v| *v = false);
             Ok(())
         }
 
         #[weight = SimpleDispatchInfo::FreeOperational]
         pub fn set_collateral_asset_id(origin, asset_id: T::AssetId) -> LoanResult {
-            ensure_root(origin)?;
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
             <CollateralAssetId<T>>::put(asset_id);
             Ok(())
         }
 
         #[weight = SimpleDispatchInfo::FreeOperational]
         pub fn set_global_ltv_limit(origin, limit: LTV) -> LoanResult {
-            ensure_root(origin)?;
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
             GlobalLTVLimit::put(limit);
             Ok(())
         }
 
         #[weight = SimpleDispatchInfo::FreeOperational]
         pub fn set_loan_asset_id(origin, asset_id: T::AssetId) -> LoanResult {
-            ensure_root(origin)?;
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
             <LoanAssetId<T>>::put(asset_id);
             Ok(())
         }
 
         #[weight = SimpleDispatchInfo::FreeOperational]
         pub fn set_global_liquidation_threshold(origin, threshold: LTV) -> LoanResult {
-            ensure_root(origin)?;
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
             GlobalWarningThreshold::put(threshold);
             Ok(())
         }
 
         #[weight = SimpleDispatchInfo::FreeOperational]
         pub fn set_global_warning_threshold(origin, threshold: LTV) -> LoanResult {
-            ensure_root(origin)?;
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
             GlobalLiquidationThreshold::put(threshold);
             Ok(())
         }
 
         #[weight = SimpleDispatchInfo::FreeOperational]
         pub fn set_loan_cap(origin, balance: T::Balance) -> LoanResult {
-            ensure_root(origin)?;
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
             if balance.is_zero() {
                 <LoanCap<T>>::kill();
             } else {
@@ -368,14 +513,39 @@ decl_module! {
 
         #[weight = SimpleDispatchInfo::FreeOperational]
         pub fn set_liquidation_account(origin, account_id: T::AccountId) -> LoanResult {
-            ensure_root(origin)?;
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
             <LiquidationAccount<T>>::put(account_id);
             Ok(())
         }
 
+        /// once a liquidation misses its deadline, it's handed off to "backup" if any, or restarted otherwise,
+        /// and the loan can be repaid with the liquidation penalty
+        #[weight = SimpleDispatchInfo::FreeOperational]
+        pub fn set_liquidation_timeout(origin, timeout: T::BlockNumber, backup: Option<T::AccountId>) -> LoanResult {
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
+            <LiquidationTimeout<T>>::put(timeout);
+            // liquidations started without a timeout get their deadline from now on, a zero timeout drops them all
+            for loan_id in Self::liquidating_loans() {
+                let mut record = Self::liquidation_record(loan_id);
+                if timeout.is_zero() {
+                    record.deadline = None;
+                } else if record.deadline.is_none() {
+                    record.deadline = Self::schedule_liquidation_deadline(loan_id);
+                } else {
+                    continue;
+                }
+                <LiquidationRecords<T>>::insert(loan_id, record);
+            }
+            match backup {
+                Some(account) => <BackupLiquidationAccount<T>>::put(account),
+                None => <BackupLiquidationAccount<T>>::kill(),
+            }
+            Ok(())
+        }
+
         #[weight = SimpleDispatchInfo::FreeOperational]
         pub fn set_penalty_rate(origin, rate: u32) -> LoanResult {
-            ensure_root(origin)?;
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
             PenaltyRate::put(rate);
             Ok(())
         }
@@ -384,7 +554,7 @@ decl_module! {
         #[weight = SimpleDispatchInfo::FreeOperational]
         pub fn create_package(origin, terms: u32, interest_rate_hourly: u32, min_tbd: T::Balance) -> LoanResult {
             ensure!(!Self::paused(), "module is paused");
-            ensure_root(origin)?;
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
             ensure!(terms > 0 && interest_rate_hourly > 0 && min_tbd > T::Balance::zero(), "zero is not allowed");
             ensure!(interest_rate_hourly < INTEREST_RATE_PREC, "invalid interest rate");
             Self::create_loan_package(terms, interest_rate_hourly, min_tbd)
@@ -394,15 +564,25 @@ decl_module! {
         #[weight = SimpleDispatchInfo::FreeOperational]
         pub fn disable_package(origin, package_id: LoanPackageId) -> LoanResult {
             ensure!(!Self::paused(), "module is paused");
-            ensure_root(origin)?;
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
             Self::disable_loan_package(package_id)
         }
 
+        /// zero "rate" disables rebates of the package, which is the default
+        #[weight = SimpleDispatchInfo::FreeOperational]
+        pub fn set_rebate_rate(origin, package_id: LoanPackageId, rate: u32) -> LoanResult {
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
+            ensure!(<LoanPackages<T>>::exists(package_id), "invalid package id");
+            ensure!(rate <= LTV_PREC, "invalid rebate rate");
+            RebateRates::insert(package_id, rate);
+            Ok(())
+        }
+
         /// a backdoor to manually set BTC price
         #[weight = SimpleDispatchInfo::FreeOperational]
         pub fn set_price(origin, price: Price) -> LoanResult {
-            ensure_root(origin)?;
-            CurrentBTCPrice::put(price);
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
+            Self::update_price(price);
             Ok(())
         }
 
@@ -413,6 +593,30 @@ decl_module! {
             Self::repay_loan(ensure_signed(origin)?, loan_id)
         }
 
+        /// anyone can pay off a loan for its owner, the collateral always returns to the loan's owner
+        /// an "amount" below the loan balance only tops up the loan by paying off that much of the debt
+        #[weight = SimpleDispatchInfo::FixedNormal(1000_000)]
+        pub fn repay_for(origin, loan_id: LoanId, amount: T::Balance) -> DispatchResult {
+            ensure!(!Self::paused(), "module is paused");
+            let payer = ensure_signed(origin)?;
+            ensure!(!amount.is_zero(), "zero is not allowed");
+            ensure!(<Loans<T>>::exists(loan_id), "invalid loan id");
+            if amount >= Self::get_loan_by_id(loan_id).loan_balance_total {
+                Self::repay_loan_by(payer, loan_id)
+            } else {
+                Self::top_up_loan(payer, loan_id, amount)
+            }
+        }
+
+        /// move a loan to another active package without repaying it, the collateral stays in the pawnshop
+        /// the difference of the two packages' interests is paid to or refunded from "ProfitPool",
+        /// and the loan is due again from now by the terms of the new package
+        #[weight = SimpleDispatchInfo::FixedNormal(1000_000)]
+        pub fn refinance(origin, loan_id: LoanId, new_package_id: LoanPackageId) -> DispatchResult {
+            ensure!(!Self::paused(), "module is paused");
+            Self::refinance_loan(ensure_signed(origin)?, loan_id, new_package_id)
+        }
+
         /// a user can apply for a loan choosing one active loan package, providing the collateral and loan amount he wants,
         #[weight = SimpleDispatchInfo::FixedNormal(1000_000)]
         pub fn apply(origin, collateral_amount: T::Balance, loan_amount: T::Balance, package_id: LoanPackageId) -> LoanResult {
@@ -427,8 +631,12 @@ decl_module! {
         pub fn mark_liquidated(origin, loan_id: LoanId, auction_balance: T::Balance) -> DispatchResult {
             ensure!(!Self::paused(), "module is paused");
             let liquidation_account = ensure_signed(origin)?;
-            ensure!(liquidation_account == Self::liquidation_account(), "liquidation account only");
             ensure!(<Loans<T>>::exists(loan_id), "loan doesn't exists");
+            ensure!(Self::check_loan_in_liquidation(&loan_id), "loan id not in liquidating");
+            ensure!(
+                liquidation_account == Self::liquidation_record(loan_id).liquidator,
+                "liquidation account only"
+            );
 
             Self::mark_loan_liquidated(&Self::get_loan_by_id(loan_id), liquidation_account, auction_balance)
         }
@@ -445,6 +653,142 @@ decl_module! {
             Self::add_loan_collateral(&loan, loan.who.clone(), amount)
         }
 
+        /// *admin only*
+        #[weight = SimpleDispatchInfo::FreeOperational]
+        pub fn set_insurance(origin, fund: Option<T::AccountId>, rate: u32) -> DispatchResult {
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
+            ensure!(rate <= LTV_PREC, "invalid insurance rate");
+            match fund {
+                Some(account) => <InsuranceFund<T>>::put(account),
+                None => <InsuranceFund<T>>::kill(),
+            }
+            InsuranceRate::put(rate);
+            Ok(())
+        }
+
+        /// the market maker spends no more than it has approved to the module account, see "self_liquidate"
+        /// *admin only*
+        #[weight = SimpleDispatchInfo::FreeOperational]
+        pub fn set_self_liquidation(origin, market_maker: Option<T::AccountId>, discount: u32, fee_rate: u32, max_price_age: T::Moment) -> DispatchResult {
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
+            ensure!(discount <= MAX_SELF_LIQUIDATION_DISCOUNT, "invalid discount");
+            ensure!(fee_rate < Self::liquidation_penalty(), "invalid fee rate");
+            match market_maker {
+                Some(account) => <SelfLiquidationMarketMaker<T>>::put(account),
+                None => <SelfLiquidationMarketMaker<T>>::kill(),
+            }
+            SelfLiquidationDiscount::put(discount);
+            SelfLiquidationFee::put(fee_rate);
+            <SelfLiquidationMaxPriceAge<T>>::put(max_price_age);
+            Ok(())
+        }
+
+        /// sell just enough collateral to the market maker to pay off the loan and the self liquidation fee,
+        /// the rest of the collateral, no less than "min_out", returns to the owner
+        /// the market maker pays out of the allowance it has approved to the module account
+        #[weight = SimpleDispatchInfo::FixedNormal(1000_000)]
+        pub fn self_liquidate(origin, loan_id: LoanId, min_out: T::Balance) -> DispatchResult {
+            ensure!(!Self::paused(), "module is paused");
+            let who = ensure_signed(origin)?;
+            ensure!(<Loans<T>>::exists(loan_id), "invalid loan id");
+            ensure!(Self::get_loan_by_id(loan_id).who == who, "not owner of the loan");
+            Self::self_liquidate_loan(loan_id, min_out)
+        }
+
+        /// zero "cap" disables flash mints, "fee_rate" is in LTV_PREC of the minted amount
+        #[weight = SimpleDispatchInfo::FreeOperational]
+        pub fn set_flash_mint(origin, cap: T::Balance, fee_rate: u32) -> DispatchResult {
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
+            ensure!(fee_rate < LTV_PREC, "invalid fee rate");
+            <FlashMintCap<T>>::put(cap);
+            FlashMintFeeRate::put(fee_rate);
+            Ok(())
+        }
+
+        /// mint "amount" TBD to the caller and dispatch "callback" as the caller
+        /// "amount" plus the fee is pulled from the caller up front, through the allowance given to the module account,
+        /// and held until the callback returns, then "amount" of it is burnt, so the minted TBD stays backed
+        /// whatever the callback does with it
+        /// the fee goes to "ProfitPool" if the callback succeeds, it's given back otherwise and the dispatch fails
+        #[weight = SimpleDispatchInfo::FixedNormal(1000_000)]
+        pub fn flash_mint(origin, amount: T::Balance, callback: Box<<T as Trait>::Callback>) -> DispatchResult {
+            ensure!(!Self::paused(), "module is paused");
+            let who = ensure_signed(origin)?;
+            ensure!(!amount.is_zero(), "zero is not allowed");
+            ensure!(amount <= Self::flash_mint_cap(), "exceed flash mint cap");
+            ensure!(!Self::flash_minting(), "flash mints can't be nested");
+
+            let asset_id = Self::loan_asset_id();
+            let fee = amount
+                .checked_mul(&T::Balance::from(Self::flash_mint_fee_rate()))
+                .ok_or("flash mint fee overflows")?
+                / T::Balance::from(LTV_PREC);
+            let escrow = amount.checked_add(&fee).ok_or("flash mint fee overflows")?;
+            let account = Self::account_id();
+            ensure!(
+                <assets::Module<T>>::allowance(&asset_id, &(who.clone(), account.clone())) >= escrow,
+                "flash mint allowance is not enough"
+            );
+            <assets::Module<T>>::make_transfer_with_event(&asset_id, &who, &account, escrow)?;
+            <assets::Module<T>>::make_mint(&asset_id, &who, amount).or_else(|err| -> DispatchResult {
+                <assets::Module<T>>::make_transfer_with_event(&asset_id, &account, &who, escrow)?;
+                Err(err)
+            })?;
+            <assets::Module<T>>::spend_allowance(&asset_id, &who, &account, escrow)?;
+
+            FlashMinting::put(true);
+            let callback_ok = callback.dispatch(system::RawOrigin::Signed(who.clone()).into()).is_ok();
+            FlashMinting::kill();
+
+            <assets::Module<T>>::make_burn(&asset_id, &account, amount)?;
+            if !callback_ok {
+                if !fee.is_zero() {
+                    <assets::Module<T>>::make_transfer_with_event(&asset_id, &account, &who, fee)?;
+                }
+                return Err("flash mint callback failed");
+            }
+            if !fee.is_zero() {
+                <assets::Module<T>>::make_transfer_with_event(&asset_id, &account, &Self::profit_pool(), fee)?;
+                <TotalProfit<T>>::mutate(|v| *v += fee);
+            }
+            Self::deposit_event(RawEvent::FlashMinted(who, amount, fee));
+            Ok(())
+        }
+
+        /// offer a loan, its debt and its collateral, to "to", which takes over once "to" accepts it
+        /// a new offer replaces the previous one
+        #[weight = SimpleDispatchInfo::FixedNormal(1000_000)]
+        pub fn offer_loan_transfer(origin, loan_id: LoanId, to: T::AccountId) -> DispatchResult {
+            ensure!(!Self::paused(), "module is paused");
+            let who = ensure_signed(origin)?;
+            ensure!(<Loans<T>>::exists(loan_id), "invalid loan id");
+            ensure!(Self::get_loan_by_id(loan_id).who == who, "not owner of the loan");
+            ensure!(!Self::check_loan_in_liquidation(&loan_id), "loan is in liquidation");
+            ensure!(to != who, "can't transfer a loan to its owner");
+
+            <LoanTransferOffers<T>>::insert(loan_id, to.clone());
+            Self::deposit_event(RawEvent::LoanTransferOffered(loan_id, who, to));
+            Ok(())
+        }
+
+        /// take over a loan offered to the caller
+        #[weight = SimpleDispatchInfo::FixedNormal(1000_000)]
+        pub fn accept_loan_transfer(origin, loan_id: LoanId) -> DispatchResult {
+            ensure!(!Self::paused(), "module is paused");
+            let who = ensure_signed(origin)?;
+            ensure!(Self::loan_transfer_offer(loan_id) == Some(who.clone()), "loan is not offered to you");
+            ensure!(<Loans<T>>::exists(loan_id), "invalid loan id");
+            ensure!(!Self::check_loan_in_liquidation(&loan_id), "loan is in liquidation");
+            let from = Self::get_loan_by_id(loan_id).who;
+            let class_id = Self::loan_class_id()?;
+            // loans opened before loan tokens have none, they are handed over directly
+            if <nft::Module<T>>::owner_of((class_id, loan_id)).is_some() {
+                <nft::Module<T>>::make_transfer(class_id, loan_id, &from, &who)
+            } else {
+                Self::transfer_loan(loan_id, who)
+            }
+        }
+
         /// as long as the LTV of this loan is below the "GlobalLTVLimit", user can keep drawing TBD from this loan
         #[weight = SimpleDispatchInfo::FixedNormal(1000_000)]
         pub fn draw(origin, loan_id: LoanId, amount: T::Balance) -> DispatchResult {
@@ -486,6 +830,30 @@ decl_event!(
         ),
 
         AddCollateral(LoanId, Balance),
+        /// (loan id, liquidator, escalations so far)
+        LiquidationEscalated(LoanId, AccountId, u32),
+        /// (loan id, liquidation penalty)
+        RepaidInLiquidation(LoanId, Balance),
+        /// (account, amount)
+        InsuranceFunded(AccountId, Balance),
+        /// (loan id, shortfall covered by the insurance fund)
+        ShortfallCovered(LoanId, Balance),
+        /// (loan id, shortfall nobody covered)
+        BadDebt(LoanId, Balance),
+        /// (loan id, collateral sold, TBD repaid, collateral returned)
+        SelfLiquidated(LoanId, Balance, Balance, Balance),
+        /// (loan id, owner, rebate of the prepaid interest)
+        Rebated(LoanId, AccountId, Balance),
+        /// (loan id, payer, amount)
+        LoanToppedUp(LoanId, AccountId, Balance),
+        /// (loan id, from, to)
+        LoanTransferOffered(LoanId, AccountId, AccountId),
+        /// (loan id, from, to)
+        LoanTransferred(LoanId, AccountId, AccountId),
+        /// (loan id, old package id, new package id)
+        Refinanced(LoanId, LoanPackageId, LoanPackageId),
+        /// (who, amount, fee)
+        FlashMinted(AccountId, Balance, Balance),
     }
 );
 
@@ -527,18 +895,44 @@ impl<T: Trait> Module<T> {
     }
 
     pub fn repay_loan(who: T::AccountId, loan_id: LoanId) -> DispatchResult {
+        ensure!(<Loans<T>>::exists(loan_id), "invalid loan id");
+        ensure!(<Loans<T>>::get(loan_id).who == who, "not owner of the loan");
+        Self::repay_loan_by(who, loan_id)
+    }
+
+    /// "payer" pays off the whole loan, the collateral returns to the loan's owner
+    pub fn repay_loan_by(payer: T::AccountId, loan_id: LoanId) -> DispatchResult {
         let pawn_shop = Self::pawn_shop();
         ensure!(<Loans<T>>::exists(loan_id), "invalid loan id");
         let loan = <Loans<T>>::get(loan_id);
-        ensure!(loan.who == who, "not owner of the loan");
+        let who = loan.who.clone();
         ensure!(
             <LoanPackages<T>>::exists(loan.package_id),
             "invalid package id in loan"
         );
         let package = Self::loan_package(loan.package_id);
+        // a loan whose liquidation has missed a deadline can be repaid with the liquidation penalty
+        let in_liquidation = Self::check_loan_in_liquidation(&loan_id);
         ensure!(
-            <assets::Module<T>>::free_balance(&package.loan_asset_id, &who)
-                >= loan.loan_balance_total,
+            !in_liquidation || Self::liquidation_record(loan_id).escalations > 0,
+            "loan is in liquidation"
+        );
+        let penalty = if in_liquidation {
+            loan.loan_balance_total * T::Balance::from(Self::liquidation_penalty())
+                / T::Balance::from(LTV_PREC)
+        } else {
+            T::Balance::zero()
+        };
+        let profit_pool = Self::profit_pool();
+        let rebate = if in_liquidation {
+            T::Balance::zero()
+        } else {
+            Self::early_repayment_rebate(&loan, <timestamp::Module<T>>::get())
+                .min(loan.interest_reserved)
+        };
+        ensure!(
+            <assets::Module<T>>::free_balance(&package.loan_asset_id, &payer)
+                >= loan.loan_balance_total + penalty,
             "not enough asset to repay"
         );
         ensure!(
@@ -546,11 +940,8 @@ impl<T: Trait> Module<T> {
                 >= loan.collateral_balance_available,
             "not enough collateral asset in shop"
         );
-        ensure!(
-            !Self::check_loan_in_liquidation(&loan_id),
-            "loan is in liquidation"
-        );
 
+        let holder = Self::burn_loan_token(loan_id)?;
         <Loans<T>>::remove(&loan.id);
         <LoansByAccount<T>>::mutate(&who, |v| {
             *v = v
@@ -568,11 +959,12 @@ impl<T: Trait> Module<T> {
             });
             <TotalLoan<T>>::mutate(|v| *v += loan.loan_balance_total);
             <TotalCollateral<T>>::mutate(|v| *v += loan.collateral_balance_available);
+            Self::restore_loan_token(loan_id, &holder);
         };
 
         <assets::Module<T>>::make_transfer_with_event(
             &package.loan_asset_id,
-            &who,
+            &payer,
             &pawn_shop,
             loan.loan_balance_total,
         )
@@ -591,22 +983,226 @@ impl<T: Trait> Module<T> {
             <assets::Module<T>>::make_transfer_with_event(
                 &package.loan_asset_id,
                 &pawn_shop,
-                &who,
+                &payer,
                 loan.loan_balance_total,
             )?;
             Err(err)
         })?;
-        <assets::Module<T>>::burn(
-            system::RawOrigin::Root.into(),
-            package.loan_asset_id.clone(),
-            pawn_shop.clone(),
+        <assets::Module<T>>::make_burn(
+            &package.loan_asset_id,
+            &pawn_shop,
             loan.loan_balance_total,
         )?;
 
+        <LoanTransferOffers<T>>::remove(&loan_id);
+
+        if in_liquidation {
+            Self::end_liquidation(loan_id);
+            if !penalty.is_zero() {
+                <assets::Module<T>>::make_transfer_with_event(
+                    &package.loan_asset_id,
+                    &payer,
+                    &profit_pool,
+                    penalty,
+                )?;
+                <TotalLiquidationPenalty<T>>::mutate(|v| *v += penalty);
+                Self::fund_insurance(&package.loan_asset_id, penalty);
+            }
+            Self::deposit_event(RawEvent::RepaidInLiquidation(loan_id, penalty));
+        }
+        Self::release_reserved_interest(&loan, &package.loan_asset_id, rebate)?;
+        if !rebate.is_zero() {
+            Self::deposit_event(RawEvent::Rebated(loan_id, who.clone(), rebate));
+        }
+
         Self::deposit_event(RawEvent::LoanRepaid(loan_id));
         Ok(())
     }
 
+    pub fn self_liquidate_loan(loan_id: LoanId, min_out: T::Balance) -> DispatchResult {
+        let market_maker =
+            Self::self_liquidation_market_maker().ok_or("self liquidation is disabled")?;
+        let loan = Self::get_loan_by_id(loan_id);
+        ensure!(
+            !Self::check_loan_in_liquidation(&loan_id),
+            "loan is in liquidation"
+        );
+        ensure!(
+            Self::price_updated_at()
+                .checked_add(&Self::self_liquidation_max_price_age())
+                .map_or(false, |fresh_until| <timestamp::Module<T>>::get() <= fresh_until),
+            "price is stale"
+        );
+        let pawn_shop = Self::pawn_shop();
+        let profit_pool = Self::profit_pool();
+        let package = Self::loan_package(loan.package_id);
+
+        let fee = loan.loan_balance_total * T::Balance::from(Self::self_liquidation_fee())
+            / T::Balance::from(LTV_PREC);
+        let repaid = loan.loan_balance_total + fee;
+        // collateral sold at the discounted oracle price, rounded up
+        let discounted_price = <T::Balance as TryFrom<u128>>::try_from(Self::current_btc_price())
+            .ok()
+            .and_then(|p| p.checked_mul(&T::Balance::from(LTV_PREC - Self::self_liquidation_discount())))
+            .ok_or("price overflows")?;
+        let sold = repaid
+            .checked_mul(&T::Balance::from(PRICE_PREC))
+            .and_then(|v| v.checked_mul(&T::Balance::from(LTV_PREC)))
+            .and_then(|v| v.checked_add(&discounted_price.saturating_sub(One::one())))
+            .ok_or("amount overflows")?
+            .checked_div(&discounted_price)
+            .ok_or("price is zero")?;
+        ensure!(
+            sold <= loan.collateral_balance_available,
+            "collateral can't cover the loan"
+        );
+        let returned = loan.collateral_balance_available - sold;
+        ensure!(returned >= min_out, "returned collateral is below min_out");
+        ensure!(
+            <assets::Module<T>>::allowance(
+                &package.loan_asset_id,
+                &(market_maker.clone(), Self::account_id())
+            ) >= repaid,
+            "market maker allowance is not enough"
+        );
+        ensure!(
+            <assets::Module<T>>::free_balance(&package.loan_asset_id, &market_maker) >= repaid,
+            "market maker can't afford the collateral"
+        );
+
+        let holder = Self::burn_loan_token(loan_id)?;
+        <assets::Module<T>>::make_transfer_with_event(
+            &package.loan_asset_id,
+            &market_maker,
+            &pawn_shop,
+            repaid,
+        )
+        .or_else(|err| -> DispatchResult {
+            Self::restore_loan_token(loan_id, &holder);
+            Err(err)
+        })?;
+        <assets::Module<T>>::make_transfer_with_event(
+            &package.collateral_asset_id,
+            &pawn_shop,
+            &market_maker,
+            sold,
+        )
+        .or_else(|err| -> DispatchResult {
+            Self::restore_loan_token(loan_id, &holder);
+            <assets::Module<T>>::make_transfer_with_event(
+                &package.loan_asset_id,
+                &pawn_shop,
+                &market_maker,
+                repaid,
+            )?;
+            Err(err)
+        })?;
+        <assets::Module<T>>::make_burn(
+            &package.loan_asset_id,
+            &pawn_shop,
+            loan.loan_balance_total,
+        )?;
+        if !fee.is_zero() {
+            <assets::Module<T>>::make_transfer_with_event(
+                &package.loan_asset_id,
+                &pawn_shop,
+                &profit_pool,
+                fee,
+            )?;
+        }
+        if !returned.is_zero() {
+            <assets::Module<T>>::make_transfer_with_event(
+                &package.collateral_asset_id,
+                &pawn_shop,
+                &loan.who,
+                returned,
+            )?;
+        }
+
+        <Loans<T>>::remove(&loan_id);
+        <LoansByAccount<T>>::mutate(&loan.who, |v| {
+            *v = v
+                .clone()
+                .into_iter()
+                .filter(|ele| *ele != loan_id)
+                .collect::<Vec<LoanId>>();
+        });
+        <LoanTransferOffers<T>>::remove(&loan_id);
+        <TotalLoan<T>>::mutate(|v| *v -= loan.loan_balance_total);
+        <TotalCollateral<T>>::mutate(|v| *v -= loan.collateral_balance_available);
+        <TotalProfit<T>>::mutate(|v| *v += fee);
+        Self::release_reserved_interest(&loan, &package.loan_asset_id, T::Balance::zero())?;
+        <assets::Module<T>>::spend_allowance(
+            &package.loan_asset_id,
+            &market_maker,
+            &Self::account_id(),
+            repaid,
+        )?;
+
+        Self::deposit_event(RawEvent::SelfLiquidated(
+            loan_id,
+            sold,
+            loan.loan_balance_total,
+            returned,
+        ));
+        Ok(())
+    }
+
+    /// the share of the loan's unused prepaid interest rebated by the rebate rate of its package
+    pub fn early_repayment_rebate(
+        loan: &Loan<T::AccountId, T::Balance, T::Moment>,
+        now: T::Moment,
+    ) -> T::Balance {
+        loan.unused_interest(now) * T::Balance::from(Self::rebate_rate(loan.package_id))
+            / T::Balance::from(LTV_PREC)
+    }
+
+    /// "payer" pays off part of the loan's debt, e.g. to save it from liquidation
+    pub fn top_up_loan(payer: T::AccountId, loan_id: LoanId, amount: T::Balance) -> DispatchResult {
+        ensure!(<Loans<T>>::exists(loan_id), "invalid loan id");
+        let loan = <Loans<T>>::get(loan_id);
+        ensure!(
+            amount < loan.loan_balance_total,
+            "amount covers the whole loan"
+        );
+        ensure!(
+            !Self::check_loan_in_liquidation(&loan_id),
+            "loan is in liquidation"
+        );
+        let pawn_shop = Self::pawn_shop();
+        let package = Self::loan_package(loan.package_id);
+        ensure!(
+            <assets::Module<T>>::free_balance(&package.loan_asset_id, &payer) >= amount,
+            "not enough asset to repay"
+        );
+
+        <assets::Module<T>>::make_transfer_with_event(
+            &package.loan_asset_id,
+            &payer,
+            &pawn_shop,
+            amount,
+        )?;
+        <assets::Module<T>>::make_burn(&package.loan_asset_id, &pawn_shop, amount).or_else(
+            |err| -> DispatchResult {
+                <assets::Module<T>>::make_transfer_with_event(
+                    &package.loan_asset_id,
+                    &pawn_shop,
+                    &payer,
+                    amount,
+                )?;
+                Err(err)
+            },
+        )?;
+
+        <Loans<T>>::mutate(loan_id, |v| {
+            v.loan_balance_total -= amount;
+        });
+        <TotalLoan<T>>::mutate(|v| *v -= amount);
+
+        Self::deposit_event(RawEvent::LoanToppedUp(loan_id, payer, amount));
+        Ok(())
+    }
+
     pub fn draw_from_loan(
         who: T::AccountId,
         loan_id: LoanId,
@@ -633,40 +1229,127 @@ impl<T: Trait> Module<T> {
         let package = Self::loan_package(loan.package_id);
         let interest = package.get_interest(amount);
 
-        <assets::Module<T>>::mint(
-            system::RawOrigin::Root.into(),
-            package.loan_asset_id.clone(),
-            profit_pool.clone(),
-            interest,
-        )?;
+        <assets::Module<T>>::make_mint(&package.loan_asset_id, &profit_pool, interest)?;
 
-        <assets::Module<T>>::mint(
-            system::RawOrigin::Root.into(),
-            package.loan_asset_id.clone(),
-            who.clone(),
-            amount - interest,
-        )
-        .or_else(|err| -> DispatchResult {
-            <assets::Module<T>>::burn(
-                system::RawOrigin::Root.into(),
-                package.loan_asset_id.clone(),
-                profit_pool.clone(),
-                interest,
-            )?;
-            Err(err)
-        })?;
+        <assets::Module<T>>::make_mint(&package.loan_asset_id, &who, amount - interest).or_else(
+            |err| -> DispatchResult {
+                <assets::Module<T>>::make_burn(&package.loan_asset_id, &profit_pool, interest)?;
+                Err(err)
+            },
+        )?;
 
+        let reserved = Self::collect_interest(loan.package_id, &package.loan_asset_id, interest);
         <Loans<T>>::mutate(loan_id, |v| {
             v.loan_balance_total += amount;
+            v.interest_prepaid += interest;
+            v.interest_reserved += reserved;
         });
         <TotalLoan<T>>::mutate(|v| *v += amount);
-        <TotalProfit<T>>::mutate(|v| *v += interest);
 
         Self::deposit_event(RawEvent::LoanDrawn(loan_id, amount));
 
         Ok(())
     }
 
+    fn transfer_loan(loan_id: LoanId, to: T::AccountId) -> DispatchResult {
+        let from = Self::get_loan_by_id(loan_id).who;
+        <LoansByAccount<T>>::mutate(&from, |v| {
+            *v = v
+                .clone()
+                .into_iter()
+                .filter(|ele| *ele != loan_id)
+                .collect::<Vec<LoanId>>();
+        });
+        <LoansByAccount<T>>::mutate(&to, |v| {
+            v.push(loan_id);
+        });
+        <Loans<T>>::mutate(loan_id, |v| {
+            v.who = to.clone();
+        });
+        <LoanTransferOffers<T>>::remove(loan_id);
+
+        Self::deposit_event(RawEvent::LoanTransferred(loan_id, from, to));
+        Ok(())
+    }
+
+    pub fn refinance_loan(
+        who: T::AccountId,
+        loan_id: LoanId,
+        new_package_id: LoanPackageId,
+    ) -> DispatchResult {
+        ensure!(<Loans<T>>::exists(loan_id), "invalid loan id");
+        let loan = Self::get_loan_by_id(loan_id);
+        ensure!(loan.who == who, "not owner of the loan");
+        ensure!(
+            !Self::check_loan_in_liquidation(&loan_id),
+            "loan is in liquidation"
+        );
+        ensure!(
+            loan.package_id != new_package_id,
+            "loan is already in this package"
+        );
+        ensure!(
+            <ActiveLoanPackages<T>>::exists(new_package_id),
+            "invalid package id"
+        );
+        let package = Self::loan_package(loan.package_id);
+        let new_package = <ActiveLoanPackages<T>>::get(new_package_id);
+        ensure!(
+            new_package.min <= loan.loan_balance_total,
+            "not reach min loan amount"
+        );
+
+        let new_interest = new_package.get_interest(loan.loan_balance_total);
+        ensure!(
+            new_interest < loan.loan_balance_total,
+            "interest is too high"
+        );
+
+        // the new term is paid in full, only the unused part of the current one is refunded
+        let now = <timestamp::Module<T>>::get();
+        let refund = loan.unused_interest(now);
+        let profit_pool = Self::profit_pool();
+        Self::release_reserved_interest(&loan, &package.loan_asset_id, T::Balance::zero())?;
+        if new_interest > refund {
+            <assets::Module<T>>::make_transfer_with_event(
+                &new_package.loan_asset_id,
+                &who,
+                &profit_pool,
+                new_interest - refund,
+            )?;
+        } else if refund > new_interest {
+            <assets::Module<T>>::make_transfer_with_event(
+                &package.loan_asset_id,
+                &profit_pool,
+                &who,
+                refund - new_interest,
+            )?;
+        }
+        <TotalProfit<T>>::mutate(|v| *v = v.saturating_sub(refund));
+        let reserved =
+            Self::collect_interest(new_package_id, &new_package.loan_asset_id, new_interest);
+
+        let (due, due_extend) = new_package.get_dues(now);
+        <Loans<T>>::mutate(loan_id, |v| {
+            v.package_id = new_package_id;
+            v.due = due;
+            v.due_extend = due_extend;
+            v.term_start = now;
+            v.interest_prepaid = new_interest;
+            v.interest_reserved = reserved;
+            if v.status == LoanHealth::Extended {
+                v.status = LoanHealth::Well;
+            }
+        });
+
+        Self::deposit_event(RawEvent::Refinanced(
+            loan_id,
+            loan.package_id,
+            new_package_id,
+        ));
+        Ok(())
+    }
+
     pub fn apply_for_loan(
         who: T::AccountId,
         package_id: LoanPackageId,
@@ -719,65 +1402,59 @@ impl<T: Trait> Module<T> {
                 let now = <timestamp::Module<T>>::get();
                 let (due, due_extend) = package.get_dues(now);
                 let loan_id = Self::get_next_loan_id();
-                let loan = Loan {
+                let mut loan = Loan {
                     id: loan_id,
                     package_id: package_id,
                     who: who.clone(),
                     due: due,
                     due_extend: due_extend,
+                    term_start: now,
                     collateral_balance_original: actual_collateral_amount,
                     collateral_balance_available: actual_collateral_amount,
                     loan_balance_total: actual_loan_amount,
+                    interest_prepaid: interest,
+                    interest_reserved: T::Balance::zero(),
                     status: Default::default(),
                 };
 
-                <assets::Module<T>>::mint(
-                    system::RawOrigin::Root.into(),
-                    package.loan_asset_id.clone(),
-                    profit_pool.clone(),
-                    interest,
-                )
-                .or_else(|err| -> DispatchResult {
-                    <assets::Module<T>>::make_transfer_with_event(
-                        &package.collateral_asset_id,
-                        &shop,
-                        &who,
-                        actual_collateral_amount,
-                    )?;
-                    Err(err)
-                })?;
-
-                <assets::Module<T>>::mint(
-                    system::RawOrigin::Root.into(),
-                    package.loan_asset_id.clone(),
-                    who.clone(),
-                    actual_loan_amount - interest,
-                )
-                .or_else(|err| {
-                    <assets::Module<T>>::burn(
-                        system::RawOrigin::Root.into(),
-                        package.loan_asset_id.clone(),
-                        profit_pool.clone(),
-                        interest,
-                    )
-                    .and_then(|()| -> DispatchResult {
+                <assets::Module<T>>::make_mint(&package.loan_asset_id, &profit_pool, interest)
+                    .or_else(|err| -> DispatchResult {
                         <assets::Module<T>>::make_transfer_with_event(
                             &package.collateral_asset_id,
                             &shop,
                             &who,
                             actual_collateral_amount,
-                        )
+                        )?;
+                        Err(err)
                     })?;
+
+                <assets::Module<T>>::make_mint(
+                    &package.loan_asset_id,
+                    &who,
+                    actual_loan_amount - interest,
+                )
+                .or_else(|err| {
+                    <assets::Module<T>>::make_burn(&package.loan_asset_id, &profit_pool, interest)
+                        .and_then(|()| -> DispatchResult {
+                            <assets::Module<T>>::make_transfer_with_event(
+                                &package.collateral_asset_id,
+                                &shop,
+                                &who,
+                                actual_collateral_amount,
+                            )
+                        })?;
                     Err(err)
                 })?;
 
+                loan.interest_reserved =
+                    Self::collect_interest(package_id, &package.loan_asset_id, interest);
                 <Loans<T>>::insert(loan_id, loan.clone());
                 <LoansByAccount<T>>::mutate(&who, |v| {
                     v.push(loan_id);
                 });
+                <nft::Module<T>>::mint(Self::loan_class_id()?, loan_id, &who)?;
                 <TotalLoan<T>>::mutate(|v| *v += actual_loan_amount);
                 <TotalCollateral<T>>::mutate(|v| *v += actual_collateral_amount);
-                <TotalProfit<T>>::mutate(|v| *v += interest);
 
                 Self::deposit_event(RawEvent::LoanCreated(loan));
                 Ok(())
@@ -801,12 +1478,19 @@ impl<T: Trait> Module<T> {
                 >= auction_balance,
             "not enough asset to liquidate"
         );
+        // a shortfall is covered by the insurance fund or recorded as bad debt below
+        let paid = auction_balance.min(loan.loan_balance_total);
+        let holder = Self::burn_loan_token(loan.id)?;
         <assets::Module<T>>::make_transfer_with_event(
             &package.loan_asset_id,
             &liquidation_account,
             &pawnshop,
-            loan.loan_balance_total,
-        )?;
+            paid,
+        )
+        .or_else(|err| -> DispatchResult {
+            Self::restore_loan_token(loan.id, &holder);
+            Err(err)
+        })?;
         let leftover = auction_balance.checked_sub(&loan.loan_balance_total);
         if leftover.is_some() && leftover.unwrap() > T::Balance::zero() {
             let penalty_rate = Self::liquidation_penalty();
@@ -819,11 +1503,12 @@ impl<T: Trait> Module<T> {
                 penalty,
             )
             .or_else(|err| -> DispatchResult {
+                Self::restore_loan_token(loan.id, &holder);
                 <assets::Module<T>>::make_transfer_with_event(
                     &package.loan_asset_id,
                     &pawnshop,
                     &liquidation_account,
-                    loan.loan_balance_total,
+                    paid,
                 )?;
                 Err(err)
             })?;
@@ -835,6 +1520,7 @@ impl<T: Trait> Module<T> {
             )
             .or_else(|err| -> DispatchResult {
                 // revert previous transfer
+                Self::restore_loan_token(loan.id, &holder);
                 <assets::Module<T>>::make_transfer_with_event(
                     &package.loan_asset_id,
                     &Self::profit_pool(),
@@ -845,12 +1531,28 @@ impl<T: Trait> Module<T> {
                     &package.loan_asset_id,
                     &pawnshop,
                     &liquidation_account,
-                    loan.loan_balance_total,
+                    paid,
                 )?;
                 Err(err)
             })?;
+            <TotalLiquidationPenalty<T>>::mutate(|v| *v += penalty);
+            Self::fund_insurance(&package.loan_asset_id, penalty);
         }
+        let covered = if paid < loan.loan_balance_total {
+            Self::cover_shortfall(
+                loan.id,
+                &package.loan_asset_id,
+                loan.loan_balance_total - paid,
+            )?
+        } else {
+            T::Balance::zero()
+        };
+        // the debt is settled, what was paid or covered of it leaves circulation
+        <assets::Module<T>>::make_burn(&package.loan_asset_id, &pawnshop, paid + covered)?;
+        <TotalLoan<T>>::mutate(|v| *v = v.saturating_sub(loan.loan_balance_total));
+        Self::release_reserved_interest(loan, &package.loan_asset_id, T::Balance::zero())?;
         <Loans<T>>::remove(&loan.id);
+        <LoanTransferOffers<T>>::remove(&loan.id);
         <LoansByAccount<T>>::mutate(&loan.who, |v| {
             *v = v
                 .clone()
@@ -858,13 +1560,7 @@ impl<T: Trait> Module<T> {
                 .filter(|ele| ele != &loan.id)
                 .collect::<Vec<LoanId>>();
         });
-        LiquidatingLoans::mutate(|v| {
-            *v = v
-                .clone()
-                .into_iter()
-                .filter(|ele| ele != &loan.id)
-                .collect::<Vec<LoanId>>();
-        });
+        Self::end_liquidation(loan.id);
         Self::deposit_event(RawEvent::Liquidated(
             loan.id,
             loan.collateral_balance_original,
@@ -876,6 +1572,122 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
+    fn update_price(price: Price) {
+        CurrentBTCPrice::put(price);
+        <PriceUpdatedAt<T>>::put(<timestamp::Module<T>>::get());
+    }
+
+    /// the account holding the rebatable share of the prepaid interest,
+    /// and the spender the self liquidation market maker approves its budget to
+    pub fn account_id() -> T::AccountId {
+        MODULE_ID.into_account()
+    }
+
+    /// split the interest just minted to "ProfitPool": the rebatable share is reserved in the module account,
+    /// the insurance share of the rest goes to "InsuranceFund" and what is left is profit
+    /// returns the reserved share
+    fn collect_interest(
+        package_id: LoanPackageId,
+        asset_id: &T::AssetId,
+        interest: T::Balance,
+    ) -> T::Balance {
+        let mut reserved =
+            interest * T::Balance::from(Self::rebate_rate(package_id)) / T::Balance::from(LTV_PREC);
+        if !reserved.is_zero()
+            && <assets::Module<T>>::make_transfer_with_event(
+                asset_id,
+                &Self::profit_pool(),
+                &Self::account_id(),
+                reserved,
+            )
+            .is_err()
+        {
+            reserved = T::Balance::zero();
+        }
+        let insured = Self::fund_insurance(asset_id, interest - reserved);
+        <TotalProfit<T>>::mutate(|v| *v += interest - reserved - insured);
+        reserved
+    }
+
+    /// pay the rebate of a closed loan from its reserved interest, the rest of it goes to "ProfitPool"
+    fn release_reserved_interest(
+        loan: &Loan<T::AccountId, T::Balance, T::Moment>,
+        asset_id: &T::AssetId,
+        rebate: T::Balance,
+    ) -> DispatchResult {
+        let rebate = rebate.min(loan.interest_reserved);
+        if !rebate.is_zero() {
+            <assets::Module<T>>::make_transfer_with_event(
+                asset_id,
+                &Self::account_id(),
+                &loan.who,
+                rebate,
+            )?;
+        }
+        let rest = loan.interest_reserved - rebate;
+        if !rest.is_zero() {
+            <assets::Module<T>>::make_transfer_with_event(
+                asset_id,
+                &Self::account_id(),
+                &Self::profit_pool(),
+                rest,
+            )?;
+            <TotalProfit<T>>::mutate(|v| *v += rest);
+        }
+        Ok(())
+    }
+
+    /// move the insurance share of a profit just paid to "ProfitPool" into "InsuranceFund"
+    /// returns the amount moved
+    fn fund_insurance(asset_id: &T::AssetId, profit: T::Balance) -> T::Balance {
+        if let Some(fund) = Self::insurance_fund() {
+            let amount =
+                profit * T::Balance::from(Self::insurance_rate()) / T::Balance::from(LTV_PREC);
+            if !amount.is_zero()
+                && <assets::Module<T>>::make_transfer_with_event(
+                    asset_id,
+                    &Self::profit_pool(),
+                    &fund,
+                    amount,
+                )
+                .is_ok()
+            {
+                Self::deposit_event(RawEvent::InsuranceFunded(fund, amount));
+                return amount;
+            }
+        }
+        T::Balance::zero()
+    }
+
+    /// pay what a liquidation fell short of from "InsuranceFund", and record the rest as bad debt
+    /// returns the amount covered
+    fn cover_shortfall(
+        loan_id: LoanId,
+        asset_id: &T::AssetId,
+        shortfall: T::Balance,
+    ) -> result::Result<T::Balance, &'static str> {
+        let mut covered = T::Balance::zero();
+        if let Some(fund) = Self::insurance_fund() {
+            covered = shortfall.min(<assets::Module<T>>::free_balance(asset_id, &fund));
+            if !covered.is_zero() {
+                <assets::Module<T>>::make_transfer_with_event(
+                    asset_id,
+                    &fund,
+                    &Self::pawn_shop(),
+                    covered,
+                )?;
+                Self::deposit_event(RawEvent::ShortfallCovered(loan_id, covered));
+            }
+        }
+        let uncovered = shortfall - covered;
+        if !uncovered.is_zero() {
+            <BadDebts<T>>::insert(loan_id, uncovered);
+            <TotalBadDebt<T>>::mutate(|v| *v += uncovered);
+            Self::deposit_event(RawEvent::BadDebt(loan_id, uncovered));
+        }
+        Ok(covered)
+    }
+
     pub fn add_loan_collateral(
         loan: &Loan<T::AccountId, T::Balance, T::Moment>,
         from: T::AccountId,
@@ -959,6 +1771,40 @@ impl<T: Trait> Module<T> {
         }
     }
 
+    /// the nft class of loan positions, created on first use
+    fn loan_class_id() -> Result<nft::ClassId, &'static str> {
+        match Self::loan_nft_class() {
+            Some(class_id) => Ok(class_id),
+            None => {
+                let class_id = <nft::Module<T>>::create_class(b"loan".to_vec())?;
+                LoanNftClass::put(class_id);
+                Ok(class_id)
+            }
+        }
+    }
+
+    /// burns the token of a loan and returns its holder, loans opened before loan tokens have none
+    fn burn_loan_token(loan_id: LoanId) -> Result<Option<T::AccountId>, &'static str> {
+        let class_id = match Self::loan_nft_class() {
+            Some(class_id) => class_id,
+            None => return Ok(None),
+        };
+        match <nft::Module<T>>::owner_of((class_id, loan_id)) {
+            Some(holder) => {
+                <nft::Module<T>>::burn(class_id, loan_id)?;
+                Ok(Some(holder))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// mints back a token burned by "burn_loan_token" when closing the loan fails
+    fn restore_loan_token(loan_id: LoanId, holder: &Option<T::AccountId>) {
+        if let (Some(class_id), Some(holder)) = (Self::loan_nft_class(), holder) {
+            let _ = <nft::Module<T>>::mint(class_id, loan_id, holder);
+        }
+    }
+
     fn get_next_loan_package_id() -> LoanPackageId {
         NextLoanPackageId::mutate(|v| {
             let org = *v;
@@ -975,7 +1821,7 @@ impl<T: Trait> Module<T> {
         })
     }
 
-    fn on_each_block(_height: T::BlockNumber) {
+    fn on_each_block(height: T::BlockNumber) {
         let now = <timestamp::Module<T>>::get();
         let btc_price = Self::current_btc_price();
         let liquidation_thd = Self::global_liquidation_threshold();
@@ -1003,7 +1849,7 @@ impl<T: Trait> Module<T> {
                     Self::deposit_event(RawEvent::Extended(loan_id, loan.who));
                 }
                 LoanHealth::Liquidating(l) => {
-                    Self::liquidate_loan(loan_id, l);
+                    Self::liquidate_loan(loan_id, l, now);
                     Self::deposit_event(RawEvent::Liquidating(
                         loan_id,
                         loan.who.clone(),
@@ -1032,7 +1878,7 @@ impl<T: Trait> Module<T> {
                     );
 
                     if new_ltv >= liquidation_thd {
-                        Self::liquidate_loan(loan_id, new_ltv);
+                        Self::liquidate_loan(loan_id, new_ltv, now);
                         Self::deposit_event(RawEvent::Liquidating(
                             loan_id,
                             loan.who.clone(),
@@ -1042,6 +1888,13 @@ impl<T: Trait> Module<T> {
                     } else if new_ltv >= warning_thd {
                         Self::deposit_event(RawEvent::Warning(loan_id, new_ltv));
                     } else {
+                        // the term is over, its reserved interest is earned
+                        Self::release_reserved_interest(
+                            &loan,
+                            &package.loan_asset_id,
+                            T::Balance::zero(),
+                        )
+                        .unwrap_or_default();
                         <Loans<T>>::insert(loan_id, new_loan);
                         Self::deposit_event(RawEvent::Expired(loan_id, who));
                     }
@@ -1049,6 +1902,8 @@ impl<T: Trait> Module<T> {
             }
         }
 
+        Self::escalate_liquidations(height);
+
         if !(total_penalty + total_interest).is_zero() {
             <assets::Module<T>>::make_transfer_with_event(
                 &collateral_asset_id,
@@ -1057,6 +1912,8 @@ impl<T: Trait> Module<T> {
                 total_penalty + total_interest,
             )
             .and_then(|_| {
+                // the insurance share of the expiration interest and penalty is paid in collateral
+                Self::fund_insurance(&collateral_asset_id, total_penalty + total_interest);
                 <TotalCollateral<T>>::mutate(|v| {
                     match v.checked_sub(&(total_interest + total_penalty)) {
                         Some(total) => {
@@ -1071,20 +1928,122 @@ impl<T: Trait> Module<T> {
         }
     }
 
-    fn liquidate_loan(loan_id: LoanId, liquidating_ltv: LTV) {
+    fn liquidate_loan(loan_id: LoanId, liquidating_ltv: LTV, now: T::Moment) {
         <Loans<T>>::mutate(loan_id, |v| {
             v.status = LoanHealth::Liquidating(liquidating_ltv)
         });
-        if LiquidatingLoans::exists() {
-            LiquidatingLoans::mutate(|v| v.push(loan_id));
-        } else {
-            let ll: Vec<LoanId> = vec![loan_id];
-            LiquidatingLoans::put(ll);
+        <LiquidationRecords<T>>::insert(
+            loan_id,
+            LiquidationRecord {
+                started: now,
+                deadline: Self::schedule_liquidation_deadline(loan_id),
+                liquidator: Self::liquidation_account(),
+                escalations: 0,
+            },
+        );
+    }
+
+    fn end_liquidation(loan_id: LoanId) {
+        <LiquidationRecords<T>>::remove(loan_id);
+    }
+
+    /// loans in liquidating
+    pub fn liquidating_loans() -> Vec<LoanId> {
+        <LiquidationRecords<T>>::enumerate()
+            .map(|(loan_id, _)| loan_id)
+            .collect()
+    }
+
+    /// the deadline of a liquidation escalating from the current block, None if there's no timeout
+    fn schedule_liquidation_deadline(loan_id: LoanId) -> Option<T::BlockNumber> {
+        let timeout = Self::liquidation_timeout();
+        if timeout.is_zero() {
+            return None;
+        }
+        let deadline = <system::Module<T>>::block_number() + timeout;
+        <LiquidationDeadlines<T>>::mutate(deadline, |v| v.push(loan_id));
+        Some(deadline)
+    }
+
+    /// hand liquidations whose deadline is at this block off to the backup liquidator, or restart them
+    fn escalate_liquidations(height: T::BlockNumber) {
+        let backup = Self::backup_liquidation_account();
+        for loan_id in <LiquidationDeadlines<T>>::take(height) {
+            if !<LiquidationRecords<T>>::exists(loan_id) {
+                continue;
+            }
+            let mut record = Self::liquidation_record(loan_id);
+            // the deadline has been dropped or the loan liquidated and liquidating again since
+            if record.deadline != Some(height) {
+                continue;
+            }
+            if let Some(backup) = backup.clone() {
+                record.liquidator = backup;
+            }
+            record.deadline = Self::schedule_liquidation_deadline(loan_id);
+            record.escalations += 1;
+            Self::deposit_event(RawEvent::LiquidationEscalated(
+                loan_id,
+                record.liquidator.clone(),
+                record.escalations,
+            ));
+            <LiquidationRecords<T>>::insert(loan_id, record);
+        }
+    }
+
+    /// liquidations don't escalate while the module is paused, their deadlines move on to the next block
+    fn defer_liquidation_deadlines(height: T::BlockNumber) {
+        let due = <LiquidationDeadlines<T>>::take(height);
+        if due.is_empty() {
+            return;
+        }
+        let next = height + One::one();
+        for loan_id in due.iter() {
+            if <LiquidationRecords<T>>::exists(loan_id) {
+                <LiquidationRecords<T>>::mutate(loan_id, |v| {
+                    if v.deadline == Some(height) {
+                        v.deadline = Some(next);
+                    }
+                });
+            }
+        }
+        <LiquidationDeadlines<T>>::mutate(next, |v| v.extend(due));
+    }
+
+    /// bring "Loans" written by an older version of the module up to "STORAGE_VERSION"
+    fn migrate_loans() {
+        if Self::storage_version() >= STORAGE_VERSION {
+            return;
+        }
+        <Loans<T>>::translate(
+            |k: LoanId| k,
+            |old: LoanV0<T::AccountId, T::Balance, T::Moment>| old.into(),
+        )
+        .unwrap_or_default();
+        StorageVersion::put(STORAGE_VERSION);
+    }
+
+    /// loans liquidating before liquidation records were introduced are only in "LiquidatingLoans"
+    fn migrate_liquidating_loans() {
+        let now = <timestamp::Module<T>>::get();
+        for loan_id in LiquidatingLoans::take() {
+            if <LiquidationRecords<T>>::exists(loan_id) {
+                continue;
+            }
+            <LiquidationRecords<T>>::insert(
+                loan_id,
+                LiquidationRecord {
+                    started: now,
+                    deadline: Self::schedule_liquidation_deadline(loan_id),
+                    liquidator: Self::liquidation_account(),
+                    escalations: 0,
+                },
+            );
         }
     }
 
     fn check_loan_in_liquidation(loan_id: &LoanId) -> bool {
-        LiquidatingLoans::get().contains(loan_id)
+        <LiquidationRecords<T>>::exists(loan_id)
     }
 
     fn check_loan_health(
@@ -1131,9 +2090,29 @@ impl<T: Trait> assets::traits::OnAssetTransfer<T::AssetId, T::AccountId, T::Bala
     }
 }
 
+/// the holder of a loan's token owns the loan, it repays, draws and receives the liquidation leftover
+impl<T: Trait> nft::OnTokenTransfer<T::AccountId> for Module<T> {
+    fn on_token_transfer(
+        class_id: nft::ClassId,
+        token_id: nft::TokenId,
+        _from: &T::AccountId,
+        to: &T::AccountId,
+    ) -> DispatchResult {
+        if Self::loan_nft_class() != Some(class_id) {
+            return Ok(());
+        }
+        ensure!(<Loans<T>>::exists(token_id), "invalid loan id");
+        ensure!(
+            !Self::check_loan_in_liquidation(&token_id),
+            "loan is in liquidation"
+        );
+        Self::transfer_loan(token_id, to.clone())
+    }
+}
+
 /// implement the price::OnChange hook to be aware of the price changes
 impl<T: Trait> price::OnChange for Module<T> {
     fn on_change(p: price::Price) {
-        CurrentBTCPrice::put(p);
+        Self::update_price(p);
     }
 }
@@ -181,25 +181,43 @@ impl pallet_generic_asset::Trait for TestRuntime {
     type Balance = u128;
     type AssetId = u32;
 }
+parameter_types! {
+    pub const CreationDeposit: u128 = 0;
+    pub const CreationCooldown: u64 = 0;
+}
 impl assets::Trait for TestRuntime {
     type Event = ();
+    type AdminOrigin = system::EnsureRoot<u64>;
+    type Currency = balances::Module<TestRuntime>;
+    type CreationDeposit = CreationDeposit;
+    type CreationCooldown = CreationCooldown;
     type OnAssetMint = ();
     type OnAssetCreate = ();
     type OnAssetTransfer = ();
     type OnAssetBurn = ();
+    type OnAssetReaped = ();
+    type BeforeAssetDestroy = ();
     type BeforeAssetMint = ();
     type BeforeAssetCreate = ();
     type BeforeAssetTransfer = ();
     type BeforeAssetBurn = ();
 }
+impl nft::Trait for TestRuntime {
+    type Event = ();
+    type OnTokenTransfer = Module<TestRuntime>;
+}
 impl Trait for TestRuntime {
     type Event = ();
+    type AdminOrigin = system::EnsureRoot<u64>;
+    type Callback = Call;
 }
 
 pub type LoanTest = Module<TestRuntime>;
 
 pub type SystemTest = system::Module<TestRuntime>;
 
+pub type NftTest = nft::Module<TestRuntime>;
+
 pub struct ExtBuilder {}
 
 impl Default for ExtBuilder {
@@ -12,7 +12,7 @@ use sp_runtime::{
 };
 
 use crate::mock::{
-    constants::*, new_test_ext, Call, ExtBuilder, LoanTest, Origin, SystemTest, TestEvent,
+    constants::*, new_test_ext, Call, ExtBuilder, LoanTest, NftTest, Origin, SystemTest, TestEvent,
     TestRuntime,
 };
 
@@ -212,6 +212,13 @@ fn liquidate_loan_works() {
         assert_eq!(LoanTest::liquidating_loans().len(), 1);
         let loan = LoanTest::get_loan_by_id(&loan_id);
         assert_eq!(LoanTest::liquidating_loans()[0], loan_id);
+        // the token of a loan in liquidation can't change hands
+        let class_id = LoanTest::loan_nft_class().unwrap();
+        assert_eq!(
+            NftTest::transfer(Origin::signed(ALICE), class_id, loan_id, BOB),
+            Err("loan is in liquidation")
+        );
+        assert_eq!(NftTest::owner_of((class_id, loan_id)), Some(ALICE));
 
         assert_ok!(<assets::Module<TestRuntime>>::mint(
             system::RawOrigin::Root.into(),
@@ -230,6 +237,10 @@ fn liquidate_loan_works() {
             <assets::Module<TestRuntime>>::free_balance(&TBD_ASSET_ID, &ALICE),
             870_00000000 + tbd_alice
         );
+        // the penalty is counted apart from the interest
+        assert_eq!(LoanTest::total_liquidation_penalty(), 130_00000000);
+        assert_eq!(LoanTest::total_profit(), 96000000);
+        assert_eq!(LoanTest::total_loan(), 0);
     });
 }
 
@@ -237,6 +248,872 @@ fn liquidate_loan_works() {
 #[test]
 fn add_collateral_works() {}
 
+#[test]
+fn flash_mint_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        let harmless = || Box::new(Call::Assets(assets::Call::approve(TBD_ASSET_ID, BOB, 1)));
+        assert_noop!(
+            LoanTest::flash_mint(Origin::signed(ALICE), 1000, harmless()),
+            "exceed flash mint cap"
+        );
+        assert_ok!(LoanTest::set_flash_mint(
+            system::RawOrigin::Root.into(),
+            1000,
+            100
+        ));
+        assert_noop!(
+            LoanTest::flash_mint(Origin::signed(ALICE), 1001, harmless()),
+            "exceed flash mint cap"
+        );
+
+        // 1000 plus 10 of fee is pulled up front
+        let approve = || {
+            assert_ok!(<assets::Module<TestRuntime>>::approve(
+                Origin::signed(ALICE),
+                TBD_ASSET_ID,
+                LoanTest::account_id(),
+                1010
+            ));
+        };
+        let balance = |who| <assets::Module<TestRuntime>>::free_balance(&TBD_ASSET_ID, &who);
+        assert_ok!(<assets::Module<TestRuntime>>::mint(
+            system::RawOrigin::Root.into(),
+            TBD_ASSET_ID,
+            ALICE,
+            1010
+        ));
+        assert_noop!(
+            LoanTest::flash_mint(Origin::signed(ALICE), 1000, harmless()),
+            "flash mint allowance is not enough"
+        );
+        approve();
+        assert_ok!(LoanTest::flash_mint(
+            Origin::signed(ALICE),
+            1000,
+            harmless()
+        ));
+        assert_eq!(balance(ALICE), 1000);
+        assert_eq!(balance(PROFIT_POOL), 10);
+        assert_eq!(balance(LoanTest::account_id()), 0);
+        assert_eq!(
+            <assets::Module<TestRuntime>>::total_issuance(&TBD_ASSET_ID),
+            1010
+        );
+
+        // a failed callback takes no fee
+        approve();
+        let failing = Box::new(Call::Assets(assets::Call::transfer(
+            TBD_ASSET_ID,
+            BOB,
+            5000,
+        )));
+        assert_eq!(
+            LoanTest::flash_mint(Origin::signed(ALICE), 1000, failing),
+            Err("flash mint callback failed")
+        );
+        assert_eq!(balance(ALICE), 1000);
+        assert_eq!(balance(PROFIT_POOL), 10);
+
+        // what the callback gives away is paid by the caller, the supply stays the same
+        approve();
+        let leak = Box::new(Call::Assets(assets::Call::transfer(TBD_ASSET_ID, BOB, 600)));
+        assert_ok!(LoanTest::flash_mint(Origin::signed(ALICE), 1000, leak));
+        assert_eq!(balance(ALICE), 390);
+        assert_eq!(balance(BOB), 600);
+        assert_eq!(balance(PROFIT_POOL), 20);
+        assert_eq!(
+            <assets::Module<TestRuntime>>::total_issuance(&TBD_ASSET_ID),
+            1010
+        );
+
+        // nothing is minted if the caller can't pay up front
+        approve();
+        assert!(LoanTest::flash_mint(Origin::signed(ALICE), 1000, harmless()).is_err());
+        assert_eq!(balance(ALICE), 390);
+        assert_eq!(
+            <assets::Module<TestRuntime>>::total_issuance(&TBD_ASSET_ID),
+            1010
+        );
+    });
+}
+
+#[test]
+fn refinance_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        let short_package = LoanTest::next_loan_package_id();
+        assert_ok!(LoanTest::create_package(
+            system::RawOrigin::Root.into(),
+            10,
+            100,
+            1
+        ));
+        let long_package = LoanTest::next_loan_package_id();
+        assert_ok!(LoanTest::create_package(
+            system::RawOrigin::Root.into(),
+            30,
+            100,
+            1
+        ));
+        assert_ok!(<assets::Module<TestRuntime>>::mint(
+            system::RawOrigin::Root.into(),
+            SBTC_ASSET_ID,
+            ALICE,
+            1_00000000
+        ));
+        let loan_id = LoanTest::next_loan_id();
+        assert_ok!(LoanTest::apply(
+            Origin::signed(ALICE),
+            1_00000000,
+            4000_00000000,
+            short_package
+        ));
+        let profit_pool = LoanTest::profit_pool();
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&TBD_ASSET_ID, &profit_pool),
+            96000000
+        );
+
+        assert_noop!(
+            LoanTest::refinance(Origin::signed(BOB), loan_id, long_package),
+            "not owner of the loan"
+        );
+        assert_noop!(
+            LoanTest::refinance(Origin::signed(ALICE), loan_id, short_package),
+            "loan is already in this package"
+        );
+
+        // the unused short term is refunded, the long term is paid
+        assert_ok!(LoanTest::refinance(
+            Origin::signed(ALICE),
+            loan_id,
+            long_package
+        ));
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&TBD_ASSET_ID, &profit_pool),
+            288000000
+        );
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&TBD_ASSET_ID, &ALICE),
+            4000_00000000 - 288000000
+        );
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&SBTC_ASSET_ID, &PAWN_SHOP),
+            1_00000000
+        );
+        let loan = LoanTest::get_loan_by_id(loan_id);
+        assert_eq!(loan.package_id, long_package);
+        assert_eq!(loan.due, 30 * 86400 * 1000);
+        assert_eq!(loan.due_extend, 32 * 86400 * 1000);
+
+        assert_eq!(loan.interest_prepaid, 288000000);
+
+        // and back half way through, only the unused half of the long term is refunded
+        // while the short term is paid in full
+        assert_ok!(LoanTest::disable_package(
+            system::RawOrigin::Root.into(),
+            long_package
+        ));
+        timestamp::Module::<TestRuntime>::set_timestamp(15 * 86400 * 1000);
+        assert_ok!(LoanTest::refinance(
+            Origin::signed(ALICE),
+            loan_id,
+            short_package
+        ));
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&TBD_ASSET_ID, &ALICE),
+            4000_00000000 - 288000000 + 48000000
+        );
+        assert_eq!(LoanTest::total_profit(), 240000000);
+        let loan = LoanTest::get_loan_by_id(loan_id);
+        assert_eq!(loan.term_start, 15 * 86400 * 1000);
+        assert_eq!(loan.due, 25 * 86400 * 1000);
+        assert_eq!(loan.interest_prepaid, 96000000);
+        assert_noop!(
+            LoanTest::refinance(Origin::signed(ALICE), loan_id, long_package),
+            "invalid package id"
+        );
+    });
+}
+
+#[test]
+fn loan_transfer_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        let package_id = LoanTest::next_loan_package_id();
+        assert_ok!(LoanTest::create_package(
+            system::RawOrigin::Root.into(),
+            10,
+            100,
+            1
+        ));
+        assert_ok!(<assets::Module<TestRuntime>>::mint(
+            system::RawOrigin::Root.into(),
+            SBTC_ASSET_ID,
+            ALICE,
+            1_00000000
+        ));
+        let loan_id = LoanTest::next_loan_id();
+        assert_ok!(LoanTest::apply(
+            Origin::signed(ALICE),
+            1_00000000,
+            4000_00000000,
+            package_id
+        ));
+
+        assert_noop!(
+            LoanTest::offer_loan_transfer(Origin::signed(BOB), loan_id, CHRIS),
+            "not owner of the loan"
+        );
+        assert_ok!(LoanTest::offer_loan_transfer(
+            Origin::signed(ALICE),
+            loan_id,
+            BOB
+        ));
+        assert_noop!(
+            LoanTest::accept_loan_transfer(Origin::signed(CHRIS), loan_id),
+            "loan is not offered to you"
+        );
+        assert_ok!(LoanTest::accept_loan_transfer(Origin::signed(BOB), loan_id));
+        assert_eq!(LoanTest::get_loan_by_id(loan_id).who, BOB);
+        assert_eq!(LoanTest::loans_by_account(ALICE), Vec::<LoanId>::new());
+        assert_eq!(LoanTest::loans_by_account(BOB), vec![loan_id]);
+        assert_eq!(LoanTest::loan_transfer_offer(loan_id), None);
+
+        // the new owner repays and gets the collateral
+        assert_noop!(
+            LoanTest::repay(Origin::signed(ALICE), loan_id),
+            "not owner of the loan"
+        );
+        assert_ok!(<assets::Module<TestRuntime>>::mint(
+            system::RawOrigin::Root.into(),
+            TBD_ASSET_ID,
+            BOB,
+            4000_00000000
+        ));
+        assert_ok!(LoanTest::repay(Origin::signed(BOB), loan_id));
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&SBTC_ASSET_ID, &BOB),
+            1_00000000
+        );
+    });
+}
+
+#[test]
+fn loan_token_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        let package_id = LoanTest::next_loan_package_id();
+        assert_ok!(LoanTest::create_package(
+            system::RawOrigin::Root.into(),
+            10,
+            100,
+            1
+        ));
+        assert_ok!(<assets::Module<TestRuntime>>::mint(
+            system::RawOrigin::Root.into(),
+            SBTC_ASSET_ID,
+            ALICE,
+            1_00000000
+        ));
+        let loan_id = LoanTest::next_loan_id();
+        assert_ok!(LoanTest::apply(
+            Origin::signed(ALICE),
+            1_00000000,
+            2000_00000000,
+            package_id
+        ));
+        let class_id = LoanTest::loan_nft_class().unwrap();
+        assert_eq!(NftTest::owner_of((class_id, loan_id)), Some(ALICE));
+
+        // whoever holds the token owns the loan
+        assert_ok!(NftTest::transfer(
+            Origin::signed(ALICE),
+            class_id,
+            loan_id,
+            BOB
+        ));
+        assert_eq!(LoanTest::get_loan_by_id(loan_id).who, BOB);
+        assert_eq!(LoanTest::loans_by_account(BOB), vec![loan_id]);
+        assert_noop!(
+            LoanTest::draw(Origin::signed(ALICE), loan_id, 100_00000000),
+            "can't draw from others loan"
+        );
+        assert_ok!(LoanTest::draw(Origin::signed(BOB), loan_id, 100_00000000));
+
+        assert_ok!(<assets::Module<TestRuntime>>::mint(
+            system::RawOrigin::Root.into(),
+            TBD_ASSET_ID,
+            BOB,
+            2100_00000000
+        ));
+        assert_ok!(LoanTest::repay(Origin::signed(BOB), loan_id));
+        assert_eq!(NftTest::owner_of((class_id, loan_id)), None);
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&SBTC_ASSET_ID, &BOB),
+            1_00000000
+        );
+
+        // a loan without a token, opened before loan tokens, is still transferred and repaid
+        assert_ok!(<assets::Module<TestRuntime>>::mint(
+            system::RawOrigin::Root.into(),
+            SBTC_ASSET_ID,
+            ALICE,
+            1_00000000
+        ));
+        let loan_id = LoanTest::next_loan_id();
+        assert_ok!(LoanTest::apply(
+            Origin::signed(ALICE),
+            1_00000000,
+            2000_00000000,
+            package_id
+        ));
+        assert_ok!(NftTest::burn(class_id, loan_id));
+        assert_ok!(LoanTest::offer_loan_transfer(
+            Origin::signed(ALICE),
+            loan_id,
+            BOB
+        ));
+        assert_ok!(LoanTest::accept_loan_transfer(Origin::signed(BOB), loan_id));
+        assert_eq!(LoanTest::get_loan_by_id(loan_id).who, BOB);
+        assert_ok!(<assets::Module<TestRuntime>>::mint(
+            system::RawOrigin::Root.into(),
+            TBD_ASSET_ID,
+            BOB,
+            2000_00000000
+        ));
+        assert_ok!(LoanTest::repay(Origin::signed(BOB), loan_id));
+        assert!(!<Loans<TestRuntime>>::exists(loan_id));
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&SBTC_ASSET_ID, &BOB),
+            2_00000000
+        );
+    });
+}
+
+#[test]
+fn repay_for_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        let package_id = LoanTest::next_loan_package_id();
+        assert_ok!(LoanTest::create_package(
+            system::RawOrigin::Root.into(),
+            10,
+            100,
+            1
+        ));
+        assert_ok!(<assets::Module<TestRuntime>>::mint(
+            system::RawOrigin::Root.into(),
+            SBTC_ASSET_ID,
+            ALICE,
+            1_00000000
+        ));
+        let loan_id = LoanTest::next_loan_id();
+        assert_ok!(LoanTest::apply(
+            Origin::signed(ALICE),
+            1_00000000,
+            4000_00000000,
+            package_id
+        ));
+        assert_ok!(<assets::Module<TestRuntime>>::mint(
+            system::RawOrigin::Root.into(),
+            TBD_ASSET_ID,
+            BOB,
+            5000_00000000
+        ));
+
+        // a top-up
+        assert_ok!(LoanTest::repay_for(
+            Origin::signed(BOB),
+            loan_id,
+            1000_00000000
+        ));
+        assert_eq!(
+            LoanTest::get_loan_by_id(loan_id).loan_balance_total,
+            3000_00000000
+        );
+        assert_eq!(LoanTest::total_loan(), 3000_00000000);
+
+        // pays off the rest only, the collateral goes to the owner
+        assert_ok!(LoanTest::repay_for(
+            Origin::signed(BOB),
+            loan_id,
+            5000_00000000
+        ));
+        assert!(!<Loans<TestRuntime>>::exists(loan_id));
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&TBD_ASSET_ID, &BOB),
+            1000_00000000
+        );
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&SBTC_ASSET_ID, &ALICE),
+            1_00000000
+        );
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&SBTC_ASSET_ID, &BOB),
+            0
+        );
+        assert_noop!(
+            LoanTest::repay_for(Origin::signed(BOB), loan_id, 1),
+            "invalid loan id"
+        );
+    });
+}
+
+#[test]
+fn early_repayment_rebate_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        let package_id = LoanTest::next_loan_package_id();
+        assert_ok!(LoanTest::create_package(
+            system::RawOrigin::Root.into(),
+            10,
+            100,
+            1
+        ));
+        assert_noop!(
+            LoanTest::set_rebate_rate(system::RawOrigin::Root.into(), package_id, 10001),
+            "invalid rebate rate"
+        );
+        assert_ok!(LoanTest::set_rebate_rate(
+            system::RawOrigin::Root.into(),
+            package_id,
+            5000
+        ));
+        assert_ok!(<assets::Module<TestRuntime>>::mint(
+            system::RawOrigin::Root.into(),
+            SBTC_ASSET_ID,
+            ALICE,
+            1_00000000
+        ));
+        let loan_id = LoanTest::next_loan_id();
+        assert_ok!(LoanTest::apply(
+            Origin::signed(ALICE),
+            1_00000000,
+            4000_00000000,
+            package_id
+        ));
+        assert_ok!(<assets::Module<TestRuntime>>::mint(
+            system::RawOrigin::Root.into(),
+            TBD_ASSET_ID,
+            ALICE,
+            96000000
+        ));
+        let loan = LoanTest::get_loan_by_id(loan_id);
+        assert_eq!(loan.term_start, 0);
+        assert_eq!(loan.interest_prepaid, 96000000);
+        // the rebatable half of the interest is held apart from the profit pool
+        assert_eq!(loan.interest_reserved, 48000000);
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&TBD_ASSET_ID, &LoanTest::account_id()),
+            48000000
+        );
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&TBD_ASSET_ID, &PROFIT_POOL),
+            48000000
+        );
+
+        // half of the term is left, half of its prepaid interest is rebated at 50%
+        timestamp::Module::<TestRuntime>::set_timestamp(5 * 86400 * 1000);
+        assert_ok!(LoanTest::repay(Origin::signed(ALICE), loan_id));
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&TBD_ASSET_ID, &ALICE),
+            24000000
+        );
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&TBD_ASSET_ID, &PROFIT_POOL),
+            72000000
+        );
+        assert_eq!(LoanTest::total_profit(), 72000000);
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&TBD_ASSET_ID, &LoanTest::account_id()),
+            0
+        );
+    });
+}
+
+#[test]
+fn self_liquidate_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        let package_id = LoanTest::next_loan_package_id();
+        assert_ok!(LoanTest::create_package(
+            system::RawOrigin::Root.into(),
+            10,
+            100,
+            1
+        ));
+        assert_ok!(<assets::Module<TestRuntime>>::mint(
+            system::RawOrigin::Root.into(),
+            SBTC_ASSET_ID,
+            ALICE,
+            1_00000000
+        ));
+        let loan_id = LoanTest::next_loan_id();
+        assert_ok!(LoanTest::apply(
+            Origin::signed(ALICE),
+            1_00000000,
+            4000_00000000,
+            package_id
+        ));
+        assert_noop!(
+            LoanTest::self_liquidate(Origin::signed(ALICE), loan_id, 0),
+            "self liquidation is disabled"
+        );
+        assert_noop!(
+            LoanTest::set_self_liquidation(
+                system::RawOrigin::Root.into(),
+                Some(CHRIS),
+                500,
+                1300,
+                60_000
+            ),
+            "invalid fee rate"
+        );
+        // 5% off the oracle price, 1% fee, on a price no older than a minute
+        assert_ok!(LoanTest::set_self_liquidation(
+            system::RawOrigin::Root.into(),
+            Some(CHRIS),
+            500,
+            100,
+            60_000
+        ));
+        assert_ok!(<assets::Module<TestRuntime>>::mint(
+            system::RawOrigin::Root.into(),
+            TBD_ASSET_ID,
+            CHRIS,
+            5000_00000000
+        ));
+
+        let price = LoanTest::current_btc_price() * 9500;
+        let sold = (4040_00000000 * PRICE_PREC as u128 * 10000 + price - 1) / price;
+        // the market maker pays only out of what it has approved
+        assert_ok!(<assets::Module<TestRuntime>>::approve(
+            Origin::signed(CHRIS),
+            TBD_ASSET_ID,
+            LoanTest::account_id(),
+            4039_00000000
+        ));
+        assert_noop!(
+            LoanTest::self_liquidate(Origin::signed(ALICE), loan_id, 0),
+            "market maker allowance is not enough"
+        );
+        assert_ok!(<assets::Module<TestRuntime>>::approve(
+            Origin::signed(CHRIS),
+            TBD_ASSET_ID,
+            LoanTest::account_id(),
+            5000_00000000
+        ));
+        timestamp::Module::<TestRuntime>::set_timestamp(60_001);
+        assert_noop!(
+            LoanTest::self_liquidate(Origin::signed(ALICE), loan_id, 0),
+            "price is stale"
+        );
+        let current_price = LoanTest::current_btc_price();
+        assert_ok!(LoanTest::set_price(Origin::ROOT, 0));
+        assert_noop!(
+            LoanTest::self_liquidate(Origin::signed(ALICE), loan_id, 0),
+            "price is zero"
+        );
+        assert_ok!(LoanTest::set_price(Origin::ROOT, current_price));
+        assert_noop!(
+            LoanTest::self_liquidate(Origin::signed(ALICE), loan_id, 1_00000000 - sold + 1),
+            "returned collateral is below min_out"
+        );
+        assert_ok!(LoanTest::self_liquidate(
+            Origin::signed(ALICE),
+            loan_id,
+            1_00000000 - sold
+        ));
+        assert!(!<Loans<TestRuntime>>::exists(loan_id));
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&SBTC_ASSET_ID, &ALICE),
+            1_00000000 - sold
+        );
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&SBTC_ASSET_ID, &CHRIS),
+            sold
+        );
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&TBD_ASSET_ID, &CHRIS),
+            960_00000000
+        );
+        assert_eq!(
+            <assets::Module<TestRuntime>>::allowance(TBD_ASSET_ID, (CHRIS, LoanTest::account_id())),
+            960_00000000
+        );
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&TBD_ASSET_ID, &PROFIT_POOL),
+            96000000 + 40_00000000
+        );
+        assert_eq!(LoanTest::total_loan(), 0);
+    });
+}
+
+#[test]
+fn insurance_covers_shortfall() {
+    ExtBuilder::default().build().execute_with(|| {
+        assert_ok!(LoanTest::set_insurance(
+            system::RawOrigin::Root.into(),
+            Some(DAVE),
+            5000
+        ));
+        let package_id = LoanTest::next_loan_package_id();
+        assert_ok!(LoanTest::create_package(
+            system::RawOrigin::Root.into(),
+            10,
+            100,
+            1
+        ));
+        assert_ok!(<assets::Module<TestRuntime>>::mint(
+            system::RawOrigin::Root.into(),
+            SBTC_ASSET_ID,
+            ALICE,
+            1_00000000
+        ));
+        let loan_id = LoanTest::next_loan_id();
+        assert_ok!(LoanTest::apply(
+            Origin::signed(ALICE),
+            1_00000000,
+            4000_00000000,
+            package_id
+        ));
+        // half of the interest
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&TBD_ASSET_ID, &DAVE),
+            48000000
+        );
+        assert_eq!(LoanTest::total_profit(), 48000000);
+
+        assert_ok!(LoanTest::set_price(Origin::ROOT, 1));
+        next_block();
+        let loan = LoanTest::get_loan_by_id(&loan_id);
+
+        // the auction got 1000 TBD less than the debt
+        assert_ok!(<assets::Module<TestRuntime>>::mint(
+            system::RawOrigin::Root.into(),
+            TBD_ASSET_ID,
+            BOB,
+            3000_00000000
+        ));
+        assert_ok!(LoanTest::mark_loan_liquidated(&loan, BOB, 3000_00000000));
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&TBD_ASSET_ID, &BOB),
+            0
+        );
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&TBD_ASSET_ID, &DAVE),
+            0
+        );
+        // what was paid and covered of the debt is burned
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&TBD_ASSET_ID, &PAWN_SHOP),
+            0
+        );
+        assert_eq!(LoanTest::total_loan(), 0);
+        assert_eq!(LoanTest::total_profit(), 48000000);
+        assert_eq!(LoanTest::bad_debt_of(loan_id), 1000_00000000 - 48000000);
+        assert_eq!(LoanTest::total_bad_debt(), 1000_00000000 - 48000000);
+    });
+}
+
+#[test]
+fn insurance_gets_its_share_of_expirations() {
+    ExtBuilder::default().build().execute_with(|| {
+        assert_ok!(LoanTest::set_insurance(
+            system::RawOrigin::Root.into(),
+            Some(DAVE),
+            5000
+        ));
+        let package_id = LoanTest::next_loan_package_id();
+        assert_ok!(LoanTest::create_package(
+            system::RawOrigin::Root.into(),
+            10,
+            100,
+            1
+        ));
+        assert_ok!(<assets::Module<TestRuntime>>::mint(
+            system::RawOrigin::Root.into(),
+            SBTC_ASSET_ID,
+            ALICE,
+            1_00000000
+        ));
+        let loan_id = LoanTest::next_loan_id();
+        assert_ok!(LoanTest::apply(
+            Origin::signed(ALICE),
+            1_00000000,
+            4000_00000000,
+            package_id
+        ));
+
+        let loan = LoanTest::get_loan_by_id(&loan_id);
+        let package = LoanTest::loan_package(package_id);
+        let fee = loan.expiration_penalty(LoanTest::penalty_rate())
+            + loan.expiration_interest(&package, LoanTest::current_btc_price());
+        timestamp::Module::<TestRuntime>::set_timestamp(loan.due_extend);
+        next_block();
+
+        // the expiration interest and penalty are paid in collateral, half of it goes to the insurance
+        assert_eq!(
+            LoanTest::get_loan_by_id(&loan_id).collateral_balance_available,
+            1_00000000 - fee
+        );
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&SBTC_ASSET_ID, &DAVE),
+            fee / 2
+        );
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&SBTC_ASSET_ID, &PROFIT_POOL),
+            fee - fee / 2
+        );
+    });
+}
+
+#[test]
+fn liquidation_escalates_after_deadline() {
+    ExtBuilder::default().build().execute_with(|| {
+        assert_ok!(LoanTest::set_liquidation_timeout(
+            system::RawOrigin::Root.into(),
+            10,
+            Some(CHRIS)
+        ));
+        let package_id = LoanTest::next_loan_package_id();
+        assert_ok!(LoanTest::create_package(
+            system::RawOrigin::Root.into(),
+            10,
+            100,
+            1
+        ));
+        assert_ok!(<assets::Module<TestRuntime>>::mint(
+            system::RawOrigin::Root.into(),
+            SBTC_ASSET_ID,
+            ALICE,
+            1_00000000
+        ));
+        let loan_id = LoanTest::next_loan_id();
+        assert_ok!(LoanTest::apply(
+            Origin::signed(ALICE),
+            1_00000000,
+            4000_00000000,
+            package_id
+        ));
+        assert_ok!(LoanTest::set_price(Origin::ROOT, 1));
+        next_block();
+        assert_eq!(
+            LoanTest::liquidation_record(loan_id),
+            LiquidationRecord {
+                started: 0,
+                deadline: Some(11),
+                liquidator: LIQUIDATION_ACCOUNT,
+                escalations: 0,
+            }
+        );
+        assert_ok!(<assets::Module<TestRuntime>>::mint(
+            system::RawOrigin::Root.into(),
+            TBD_ASSET_ID,
+            ALICE,
+            96000000 + 520_00000000
+        ));
+        assert_noop!(
+            LoanTest::repay(Origin::signed(ALICE), loan_id),
+            "loan is in liquidation"
+        );
+
+        // the liquidation account missed the deadline
+        for _ in 0..10 {
+            next_block();
+        }
+        assert_eq!(LoanTest::liquidation_record(loan_id).liquidator, CHRIS);
+        assert_eq!(LoanTest::liquidation_record(loan_id).deadline, Some(21));
+        assert_eq!(LoanTest::liquidation_record(loan_id).escalations, 1);
+        assert_noop!(
+            LoanTest::mark_liquidated(Origin::signed(LIQUIDATION_ACCOUNT), loan_id, 5000_00000000),
+            "liquidation account only"
+        );
+
+        // the owner repays with the liquidation penalty
+        assert_ok!(LoanTest::repay(Origin::signed(ALICE), loan_id));
+        assert!(LoanTest::liquidating_loans().is_empty());
+        assert!(!<LiquidationRecords<TestRuntime>>::exists(loan_id));
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&SBTC_ASSET_ID, &ALICE),
+            1_00000000
+        );
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&TBD_ASSET_ID, &ALICE),
+            0
+        );
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&TBD_ASSET_ID, &PROFIT_POOL),
+            96000000 + 520_00000000
+        );
+    });
+}
+
+#[test]
+fn liquidation_deadlines_follow_the_timeout() {
+    ExtBuilder::default().build().execute_with(|| {
+        let package_id = LoanTest::next_loan_package_id();
+        assert_ok!(LoanTest::create_package(
+            system::RawOrigin::Root.into(),
+            10,
+            100,
+            1
+        ));
+        assert_ok!(<assets::Module<TestRuntime>>::mint(
+            system::RawOrigin::Root.into(),
+            SBTC_ASSET_ID,
+            ALICE,
+            1_00000000
+        ));
+        let loan_id = LoanTest::next_loan_id();
+        assert_ok!(LoanTest::apply(
+            Origin::signed(ALICE),
+            1_00000000,
+            4000_00000000,
+            package_id
+        ));
+        assert_ok!(LoanTest::set_price(Origin::ROOT, 1));
+        next_block();
+        // no timeout, no deadline
+        assert_eq!(LoanTest::liquidation_record(loan_id).deadline, None);
+
+        // the deadline counts from when the timeout is set
+        for _ in 0..4 {
+            next_block();
+        }
+        assert_ok!(LoanTest::set_liquidation_timeout(
+            system::RawOrigin::Root.into(),
+            10,
+            None
+        ));
+        assert_eq!(LoanTest::liquidation_record(loan_id).deadline, Some(15));
+        assert_eq!(LoanTest::liquidations_due_at(15), vec![loan_id]);
+
+        // a loan liquidating before the records gets one on upgrade
+        <LiquidationRecords<TestRuntime>>::remove(loan_id);
+        LiquidatingLoans::put(vec![loan_id]);
+        LoanTest::migrate_liquidating_loans();
+        assert!(!LiquidatingLoans::exists());
+        assert_eq!(LoanTest::liquidating_loans(), vec![loan_id]);
+        assert_eq!(
+            LoanTest::liquidation_record(loan_id),
+            LiquidationRecord {
+                started: 0,
+                deadline: Some(15),
+                liquidator: LIQUIDATION_ACCOUNT,
+                escalations: 0,
+            }
+        );
+
+        // deadlines don't pass while the module is paused
+        for _ in 0..9 {
+            next_block();
+        }
+        assert_ok!(LoanTest::pause(system::RawOrigin::Root.into()));
+        next_block();
+        assert_eq!(LoanTest::liquidation_record(loan_id).deadline, Some(16));
+        assert_ok!(LoanTest::resume(system::RawOrigin::Root.into()));
+        next_block();
+        assert_eq!(LoanTest::liquidation_record(loan_id).escalations, 1);
+        assert_eq!(LoanTest::liquidation_record(loan_id).deadline, Some(26));
+    });
+}
+
 fn next_block() {
     SystemTest::set_block_number(SystemTest::block_number() + 1);
     LoanTest::on_initialize(SystemTest::block_number());
@@ -0,0 +1,156 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+/// This module is a small registry of non-fungible tokens.
+/// Tokens are grouped in classes, other modules create a class, then mint and burn tokens of it,
+/// e.g. the loan module mints one token per loan with the loan id as the token id.
+/// Holders transfer tokens freely, T::OnTokenTransfer lets the issuing module follow the new holder
+/// or refuse the transfer.
+use rstd::prelude::*;
+use support::{
+    decl_event, decl_module, decl_storage, dispatch::Result as DispatchResult, ensure,
+    weights::SimpleDispatchInfo,
+};
+use system::ensure_signed;
+
+mod mock;
+mod tests;
+
+pub type ClassId = u64;
+pub type TokenId = u64;
+
+/// the longest class name accepted
+pub const MAX_NAME_LEN: usize = 32;
+
+pub trait OnTokenTransfer<AccountId> {
+    fn on_token_transfer(
+        class_id: ClassId,
+        token_id: TokenId,
+        from: &AccountId,
+        to: &AccountId,
+    ) -> DispatchResult;
+}
+
+impl<AccountId> OnTokenTransfer<AccountId> for () {
+    fn on_token_transfer(
+        _class_id: ClassId,
+        _token_id: TokenId,
+        _from: &AccountId,
+        _to: &AccountId,
+    ) -> DispatchResult {
+        Ok(())
+    }
+}
+
+pub trait Trait: system::Trait {
+    type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+    /// called once a token has changed hands, an error reverts the transfer
+    type OnTokenTransfer: OnTokenTransfer<Self::AccountId>;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as Nft {
+        NextClassId get(next_class_id) : ClassId;
+        pub Classes get(class_name) : map ClassId => Option<Vec<u8>>;
+        /// (class id, token id) => holder
+        pub Tokens get(owner_of) : map (ClassId, TokenId) => Option<T::AccountId>;
+        pub TokensByOwner get(tokens_of) : map T::AccountId => Vec<(ClassId, TokenId)>;
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        fn deposit_event() = default;
+
+        /// *token holder only*
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn transfer(origin, class_id: ClassId, token_id: TokenId, to: T::AccountId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::make_transfer(class_id, token_id, &who, &to)
+        }
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as system::Trait>::AccountId,
+    {
+        ClassCreated(ClassId, Vec<u8>),
+        /// (class id, token id, holder)
+        Minted(ClassId, TokenId, AccountId),
+        /// (class id, token id, from, to)
+        Transferred(ClassId, TokenId, AccountId, AccountId),
+        Burned(ClassId, TokenId),
+    }
+);
+
+impl<T: Trait> Module<T> {
+    pub fn create_class(name: Vec<u8>) -> Result<ClassId, &'static str> {
+        ensure!(
+            !name.is_empty() && name.len() <= MAX_NAME_LEN,
+            "invalid class name"
+        );
+        let class_id = Self::next_class_id();
+        NextClassId::put(class_id + 1);
+        Classes::insert(class_id, name.clone());
+        Self::deposit_event(RawEvent::ClassCreated(class_id, name));
+        Ok(class_id)
+    }
+
+    pub fn mint(class_id: ClassId, token_id: TokenId, to: &T::AccountId) -> DispatchResult {
+        ensure!(Classes::exists(class_id), "class doesn't exist");
+        ensure!(
+            !<Tokens<T>>::exists((class_id, token_id)),
+            "token already exists"
+        );
+        <Tokens<T>>::insert((class_id, token_id), to);
+        <TokensByOwner<T>>::mutate(to, |v| v.push((class_id, token_id)));
+        Self::deposit_event(RawEvent::Minted(class_id, token_id, to.clone()));
+        Ok(())
+    }
+
+    pub fn burn(class_id: ClassId, token_id: TokenId) -> DispatchResult {
+        let owner = Self::owner_of((class_id, token_id)).ok_or("token doesn't exist")?;
+        <Tokens<T>>::remove((class_id, token_id));
+        Self::remove_from_owner(&owner, class_id, token_id);
+        Self::deposit_event(RawEvent::Burned(class_id, token_id));
+        Ok(())
+    }
+
+    pub fn make_transfer(
+        class_id: ClassId,
+        token_id: TokenId,
+        from: &T::AccountId,
+        to: &T::AccountId,
+    ) -> DispatchResult {
+        let owner = Self::owner_of((class_id, token_id)).ok_or("token doesn't exist")?;
+        ensure!(owner == *from, "not owner of the token");
+        ensure!(from != to, "can't transfer a token to its owner");
+
+        Self::move_token(class_id, token_id, from, to);
+        T::OnTokenTransfer::on_token_transfer(class_id, token_id, from, to).or_else(
+            |err| -> DispatchResult {
+                Self::move_token(class_id, token_id, to, from);
+                Err(err)
+            },
+        )?;
+        Self::deposit_event(RawEvent::Transferred(
+            class_id,
+            token_id,
+            from.clone(),
+            to.clone(),
+        ));
+        Ok(())
+    }
+
+    fn move_token(class_id: ClassId, token_id: TokenId, from: &T::AccountId, to: &T::AccountId) {
+        <Tokens<T>>::insert((class_id, token_id), to);
+        Self::remove_from_owner(from, class_id, token_id);
+        <TokensByOwner<T>>::mutate(to, |v| v.push((class_id, token_id)));
+    }
+
+    fn remove_from_owner(owner: &T::AccountId, class_id: ClassId, token_id: TokenId) {
+        <TokensByOwner<T>>::mutate(owner, |v| v.retain(|t| *t != (class_id, token_id)));
+    }
+}
@@ -0,0 +1,79 @@
+#![cfg(test)]
+
+use super::*;
+use primitives::{Blake2Hasher, H256};
+use support::{impl_outer_origin, parameter_types};
+// The testing primitives are very useful for avoiding having to work with signatures
+// or public keys. `u64` is used as the `AccountId` and no `Signature`s are required.
+
+#[allow(unused_imports)]
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup, OnFinalize, OnInitialize},
+    Perbill,
+};
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct TestRuntime;
+
+impl_outer_origin! {
+    pub enum Origin for TestRuntime {}
+}
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const MaximumBlockWeight: u32 = 1024;
+    pub const MaximumBlockLength: u32 = 2 * 1024;
+    pub const AvailableBlockRatio: Perbill = Perbill::one();
+}
+impl system::Trait for TestRuntime {
+    type Origin = Origin;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Call = ();
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type WeightMultiplierUpdate = ();
+    type Event = ();
+    type BlockHashCount = BlockHashCount;
+    type MaximumBlockWeight = MaximumBlockWeight;
+    type MaximumBlockLength = MaximumBlockLength;
+    type AvailableBlockRatio = AvailableBlockRatio;
+    type Version = ();
+}
+
+/// stands for an issuing module that refuses some holders
+pub struct RefuseLocked;
+
+impl OnTokenTransfer<u64> for RefuseLocked {
+    fn on_token_transfer(
+        _class_id: ClassId,
+        _token_id: TokenId,
+        _from: &u64,
+        to: &u64,
+    ) -> DispatchResult {
+        ensure!(*to != LOCKED, "holder is locked");
+        Ok(())
+    }
+}
+
+impl Trait for TestRuntime {
+    type Event = ();
+    type OnTokenTransfer = RefuseLocked;
+}
+
+pub type Nft = Module<TestRuntime>;
+
+pub const ALICE: u64 = 2;
+pub const BOB: u64 = 3;
+pub const LOCKED: u64 = 99;
+
+pub fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+    system::GenesisConfig::default()
+        .build_storage::<TestRuntime>()
+        .unwrap()
+        .into()
+}
@@ -0,0 +1,48 @@
+#![cfg(test)]
+
+use super::*;
+
+use runtime_io::with_externalities;
+use support::{assert_noop, assert_ok};
+
+use mock::*;
+
+#[test]
+fn mint_transfer_burn_works() {
+    with_externalities(&mut new_test_ext(), || {
+        assert_noop!(Nft::mint(0, 1, &ALICE), "class doesn't exist");
+        let class_id = Nft::create_class(b"loans".to_vec()).unwrap();
+        assert_ok!(Nft::mint(class_id, 1, &ALICE));
+        assert_noop!(Nft::mint(class_id, 1, &BOB), "token already exists");
+        assert_eq!(Nft::owner_of((class_id, 1)), Some(ALICE));
+
+        assert_noop!(
+            Nft::transfer(Origin::signed(BOB), class_id, 1, BOB),
+            "not owner of the token"
+        );
+        assert_ok!(Nft::transfer(Origin::signed(ALICE), class_id, 1, BOB));
+        assert_eq!(Nft::owner_of((class_id, 1)), Some(BOB));
+        assert_eq!(Nft::tokens_of(ALICE), vec![]);
+        assert_eq!(Nft::tokens_of(BOB), vec![(class_id, 1)]);
+
+        assert_ok!(Nft::burn(class_id, 1));
+        assert_eq!(Nft::owner_of((class_id, 1)), None);
+        assert_eq!(Nft::tokens_of(BOB), vec![]);
+        assert_noop!(Nft::burn(class_id, 1), "token doesn't exist");
+    });
+}
+
+#[test]
+fn refused_transfer_is_reverted() {
+    with_externalities(&mut new_test_ext(), || {
+        let class_id = Nft::create_class(b"loans".to_vec()).unwrap();
+        assert_ok!(Nft::mint(class_id, 1, &ALICE));
+        assert_eq!(
+            Nft::transfer(Origin::signed(ALICE), class_id, 1, LOCKED),
+            Err("holder is locked")
+        );
+        assert_eq!(Nft::owner_of((class_id, 1)), Some(ALICE));
+        assert_eq!(Nft::tokens_of(ALICE), vec![(class_id, 1)]);
+        assert_eq!(Nft::tokens_of(LOCKED), vec![]);
+    });
+}
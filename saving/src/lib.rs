@@ -12,14 +12,16 @@ use sp_runtime::traits::{
     Bounded, CheckedAdd, CheckedMul, CheckedSub, MaybeDisplay, MaybeSerializeDeserialize, Member,
     One, Saturating, SimpleArithmetic, Zero,
 };
+use sp_runtime::{ModuleId, TypeId};
 use support::{
     decl_event, decl_module, decl_storage,
     dispatch::{Parameter, Result as DispatchResult},
     ensure,
+    traits::EnsureOrigin,
     weights::SimpleDispatchInfo,
 };
 #[allow(unused_imports)]
-use system::{ensure_root, ensure_signed, Error};
+use system::{ensure_signed, Error};
 
 mod mock;
 mod tests;
@@ -47,31 +49,7 @@ pub struct IOU<AccountId, Balance, AssetId> {
     pub owner: AccountId,
 }
 
-#[derive(Encode, Decode, PartialEq, Eq, Clone, Copy)]
-#[cfg_attr(feature = "std", derive(Debug))]
-pub enum ReleaseTrigger {
-    PhaseChange,
-
-    // not implemented
-    BlockNumber(u64),
-}
-impl Default for ReleaseTrigger {
-    fn default() -> Self {
-        ReleaseTrigger::PhaseChange
-    }
-}
-
-pub trait ReleasePack {
-    type Balance;
-    type AssetId;
-    type AccountId;
-
-    fn is_empty(&self) -> bool;
-    fn release(&mut self) -> Option<Self::Balance>;
-    fn get_asset_id(&self) -> Self::AssetId;
-    fn get_owner(&self) -> Self::AccountId;
-    fn check_release_trigger(&self, t: &ReleaseTrigger) -> bool;
-}
+pub use assets::{ReleasePack, ReleaseTrigger};
 
 #[derive(Encode, Decode, Clone, Copy, Default, PartialEq, Eq)]
 #[cfg_attr(feature = "std", derive(Debug))]
@@ -232,10 +210,11 @@ where
     }
 }
 
-pub trait Trait:
-    system::Trait + assets::Trait + sudo::Trait + timestamp::Trait + price::Trait
-{
+pub trait Trait: system::Trait + assets::Trait + timestamp::Trait + price::Trait {
     type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+    /// the origin that configures the module, e.g. a collective, a multisig or sudo
+    type AdminOrigin: EnsureOrigin<Self::Origin>;
 }
 
 decl_storage! {
@@ -307,6 +286,10 @@ decl_storage! {
             <timestamp::Module<T>>::get()
         }) : T::Moment;
 
+        /// the block number at which the bonus was dispatched last time
+        /// used as the snapshot of share balances when share asset checkpoints are enabled
+        LastBonusBlock get(last_bonus_block) : T::BlockNumber;
+
         /// use "ProfitAsset" for bonus
         ProfitAssetId get(profit_asset_id) config() : T::AssetId;
 
@@ -345,21 +328,21 @@ decl_module! {
 
         #[weight = SimpleDispatchInfo::MaxNormal]
         pub fn pause(origin) -> DispatchResult {
-            ensure_root(origin)?;
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
             Paused::mutate(|v| *v = true);
             Ok(())
         }
 
         #[weight = SimpleDispatchInfo::MaxNormal]
         pub fn resume(origin) -> DispatchResult {
-            ensure_root(origin)?;
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
             Paused::mutate(|v| *v = false);
             Ok(())
         }
 
         #[weight = SimpleDispatchInfo::FreeOperational]
         pub fn set_share_asset_id(origin, asset_id: T::AssetId) -> DispatchResult {
-            ensure_root(origin)?;
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
             ensure!(<assets::Module<T>>::asset_exists(&asset_id), "invalid asset id for saving share asset");
             <ShareAssetId<T>>::put(asset_id);
             Ok(())
@@ -369,7 +352,7 @@ decl_module! {
         // set_current_phase_id may break all saving schedule
         // ***
         // pub fn set_current_phase_id(origin, new_phase_id: PhaseId) -> DispatchResult {
-        //     let _from = ensure_root(origin)?;
+        //     let _from = <T as Trait>::AdminOrigin::ensure_origin(origin)?;
         //     ensure!(<PhaseInfos<T>>::exists(new_phase_id), "Invalid phase id for Saving");
         //     let old_phase_id = Self::get_current_phase_id();
         //     CurrentPhaseId::put(new_phase_id);
@@ -379,7 +362,7 @@ decl_module! {
 
         #[weight = SimpleDispatchInfo::FreeOperational]
         pub fn set_iou_asset_id_for_phase(origin, phase_id: PhaseId, asset_id: T::AssetId) -> DispatchResult {
-            ensure_root(origin)?;
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
             ensure!(<PhaseInfos<T>>::exists(&phase_id), "invalid phase id for saving");
             ensure!(<assets::Module<T>>::asset_exists(&asset_id), "invalid iou asset id for saving");
             if <IOUAssetPhaseId<T>>::exists(&asset_id) {
@@ -392,16 +375,32 @@ decl_module! {
             Ok(())
         }
 
+        /// stop referencing the IOU asset of a finished phase once all of it has been redeemed
+        /// the asset can be destroyed in the assets module afterwards
+        #[weight = SimpleDispatchInfo::FreeOperational]
+        pub fn retire_iou_asset(origin, asset_id: T::AssetId) -> DispatchResult {
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
+            ensure!(<IOUAssetPhaseId<T>>::exists(&asset_id), "no such contract assets");
+            let phase_id = Self::iou_asset_phase_id(&asset_id);
+            ensure!(phase_id < Self::current_phase_id(), "phase is not finished yet");
+            ensure!(<assets::Module<T>>::total_issuance(&asset_id).is_zero(), "iou asset is not fully redeemed");
+            <IOUAssetPhaseId<T>>::remove(&asset_id);
+            <PhaseInfos<T>>::mutate(phase_id, |pi| {
+                pi.iou_asset_id = None;
+            });
+            Ok(())
+        }
+
         #[weight = SimpleDispatchInfo::FreeOperational]
         pub fn set_collection_account(origin, account_id: T::AccountId) -> DispatchResult {
-            ensure_root(origin)?;
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
             <CollectionAccountId<T>>::put(account_id.clone());
             Ok(())
         }
 
         #[weight = SimpleDispatchInfo::FreeOperational]
         pub fn set_collection_asset_id(origin, asset_id: T::AssetId) -> DispatchResult {
-            ensure_root(origin)?;
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
             ensure!(<assets::Module<T>>::asset_exists(&asset_id), "invalid collection asset id");
             <CollectionAssetId<T>>::put(asset_id);
             Ok(())
@@ -409,7 +408,7 @@ decl_module! {
 
         #[weight = SimpleDispatchInfo::FreeOperational]
         pub fn set_profit_asset_id(origin, asset_id: T::AssetId) -> DispatchResult {
-            ensure_root(origin)?;
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
             ensure!(<assets::Module<T>>::asset_exists(&asset_id), "invalid collection asset id");
             <ProfitAssetId<T>>::put(asset_id);
             Ok(())
@@ -417,14 +416,14 @@ decl_module! {
 
         #[weight = SimpleDispatchInfo::FreeOperational]
         pub fn set_profit_pool(origin, account_id: T::AccountId) -> DispatchResult {
-            ensure_root(origin)?;
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
             <ProfitPool<T>>::put(account_id);
             Ok(())
         }
 
         #[weight = SimpleDispatchInfo::FreeOperational]
         pub fn set_team_account_id(origin, account_id: T::AccountId) -> DispatchResult {
-            ensure_root(origin)?;
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
             <TeamAccountId<T>>::put(account_id);
             Ok(())
         }
@@ -444,7 +443,7 @@ decl_module! {
         #[weight = SimpleDispatchInfo::FixedNormal(0)]
         pub fn sudo_staking(origin, asset_id: T::AssetId, amount: T::Balance, delegatee: T::AccountId) -> DispatchResult {
             ensure!(!Self::paused(), "module is paused");
-            ensure_root(origin)?;
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
             let collection_account_id = Self::collection_account_id();
             ensure!(<CollectionAssetId<T>>::get() == asset_id, "can't collect this asset");
             ensure!(<assets::Module<T>>::free_balance(&asset_id, &delegatee) >= amount, "insufficient balance");
@@ -479,13 +478,13 @@ decl_module! {
                 <assets::Module<T>>::make_transfer_with_event(&iou_asset_id, &collection_account_id, &who, iou_asset_amount)?;
                 Err(err)
             })?;
-            <assets::Module<T>>::burn(system::RawOrigin::Root.into(), iou_asset_id.clone(), collection_account_id.clone(), iou_asset_amount)
+            <assets::Module<T>>::make_burn(&iou_asset_id, &collection_account_id, iou_asset_amount)
         }
 
         #[weight = SimpleDispatchInfo::FixedNormal(0)]
         pub fn sudo_redeem(origin, iou_asset_id: T::AssetId, iou_asset_amount: T::Balance, delegatee: T::AccountId) -> DispatchResult {
             ensure!(!Self::paused(), "module is paused");
-            ensure_root(origin)?;
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
             let share_asset_id = Self::share_asset_id();
             let collection_asset_id = Self::collection_asset_id();
             let collection_account_id = Self::collection_account_id();
@@ -505,13 +504,13 @@ decl_module! {
                 <assets::Module<T>>::make_transfer_with_event(&iou_asset_id, &collection_account_id, &delegatee, iou_asset_amount)?;
                 Err(err)
             })?;
-            <assets::Module<T>>::burn(system::RawOrigin::Root.into(), iou_asset_id.clone(), collection_account_id.clone(), iou_asset_amount)
+            <assets::Module<T>>::make_burn(&iou_asset_id, &collection_account_id, iou_asset_amount)
         }
 
         #[weight = SimpleDispatchInfo::FreeOperational]
         pub fn force_release_bonus(origin) -> DispatchResult {
             ensure!(!Self::paused(), "module is paused");
-            ensure_root(origin)?;
+            <T as Trait>::AdminOrigin::ensure_origin(origin)?;
             Self::dispatch_bonus();
             Ok(())
         }
@@ -685,10 +684,9 @@ impl<T: Trait> Module<T> {
         <ShareUnreleasedList<T>>::insert(who, nl);
 
         if !share_asset_need_to_burn.is_zero() {
-            <assets::Module<T>>::burn(
-                system::RawOrigin::Root.into(),
-                share_asset_id.clone(),
-                collection_account.clone(),
+            <assets::Module<T>>::make_burn(
+                &share_asset_id,
+                &collection_account,
                 share_asset_need_to_burn,
             )?;
         }
@@ -789,19 +787,17 @@ impl<T: Trait> Module<T> {
     }
 
     fn create_reserved(amount: T::Balance) -> DispatchResult {
-        <assets::Module<T>>::mint(
-            system::RawOrigin::Root.into(),
-            Self::reserved_mint_asset_id(),
-            Self::reserved_mint_wallet(),
+        <assets::Module<T>>::make_mint(
+            &Self::reserved_mint_asset_id(),
+            &Self::reserved_mint_wallet(),
             amount,
         )
     }
 
     fn burn_reserved(amount: T::Balance) -> DispatchResult {
-        <assets::Module<T>>::burn(
-            system::RawOrigin::Root.into(),
-            Self::reserved_mint_asset_id(),
-            Self::reserved_mint_wallet(),
+        <assets::Module<T>>::make_burn(
+            &Self::reserved_mint_asset_id(),
+            &Self::reserved_mint_wallet(),
             amount,
         )
     }
@@ -880,20 +876,14 @@ impl<T: Trait> Module<T> {
         let mut iou_balance = T::Balance::zero();
         let mut share_asset_balance = T::Balance::zero();
         if let Some(iou) = iou_mapbe {
-            <assets::Module<T>>::mint(
-                system::RawOrigin::Root.into(),
-                iou.asset_id,
-                iou.owner.clone(),
-                iou.balance,
-            )?;
+            <assets::Module<T>>::make_mint(&iou.asset_id, &iou.owner, iou.balance)?;
             iou_balance = iou.balance;
         }
         if let Some(share_pack) = share_pack_maybe {
             if let Some(share_balance) = share_pack.release() {
-                <assets::Module<T>>::mint(
-                    system::RawOrigin::Root.into(),
-                    share_pack.asset_id,
-                    share_pack.owner.clone(),
+                <assets::Module<T>>::make_mint(
+                    &share_pack.asset_id,
+                    &share_pack.owner,
                     share_balance,
                 )?;
                 share_asset_balance = share_balance;
@@ -1171,6 +1161,44 @@ impl<T: Trait> Module<T> {
         (users, amount - users)
     }
 
+    /// whether "who" is the account of a module, e.g. an AMM pool, the loan or the bridge module
+    /// their shares back other holders' positions, so they get no bonus
+    fn is_module_account(who: &T::AccountId) -> bool {
+        who.using_encoded(|d| d.starts_with(&ModuleId::TYPE_ID))
+    }
+
+    /// share holders and their balances
+    /// reuse the holder index of the assets module if it's enabled for the share asset, "AccountShares" otherwise
+    fn share_holders() -> Vec<(T::AccountId, T::Balance)> {
+        let share_asset_id = Self::share_asset_id();
+        if !<assets::Module<T>>::holder_index_enabled(&share_asset_id) {
+            return <AccountShares<T>>::enumerate().collect();
+        }
+
+        let collection_account_id = Self::collection_account_id();
+        <assets::Module<T>>::holders(
+            &share_asset_id,
+            0,
+            <assets::Module<T>>::holder_count(&share_asset_id),
+        )
+        .into_iter()
+        .filter(|who| *who != collection_account_id && !Self::is_module_account(who))
+        .map(|who| {
+            let balance = <assets::Module<T>>::free_balance(&share_asset_id, &who);
+            (who, balance)
+        })
+        .collect()
+    }
+
+    /// shares only count for the bonus if they have been held since the last bonus
+    /// the snapshot is usable only when share asset checkpoints were enabled before the last bonus
+    fn share_snapshot_block() -> Option<T::BlockNumber> {
+        let last = Self::last_bonus_block();
+        <assets::Module<T>>::checkpoints_enabled_at(&Self::share_asset_id())
+            .filter(|enabled_at| !last.is_zero() && *enabled_at <= last)
+            .map(|_| last)
+    }
+
     fn dispatch_bonus() {
         let team_account = Self::team_account_id();
         let profit_pool = Self::profit_pool();
@@ -1189,7 +1217,14 @@ impl<T: Trait> Module<T> {
         .unwrap_or_default();
 
         // give each user his bonus cut
-        for (user_id, balance) in <AccountShares<T>>::enumerate() {
+        let snapshot = Self::share_snapshot_block();
+        for (user_id, balance) in Self::share_holders() {
+            // the current balance if the balance at the snapshot is unknown
+            let balance = snapshot
+                .and_then(|at| {
+                    <assets::Module<T>>::balance_at(&Self::share_asset_id(), &user_id, at)
+                })
+                .map_or(balance, |b| balance.min(b));
             let user_bonus = balance * users / circulation;
             <assets::Module<T>>::make_transfer_with_event(
                 &profit_asset,
@@ -1200,6 +1235,7 @@ impl<T: Trait> Module<T> {
             .unwrap_or_default();
         }
 
+        <LastBonusBlock<T>>::put(<system::Module<T>>::block_number());
         Self::deposit_event(RawEvent::Bonus());
     }
 }
@@ -1299,6 +1335,42 @@ impl<T: Trait> assets::traits::OnAssetBurn<T::AssetId, T::AccountId, T::Balance>
     }
 }
 
+/// an account holding no share asset at all should not be tracked anymore
+impl<T: Trait> assets::traits::OnAssetReaped<T::AssetId, T::AccountId> for Module<T> {
+    fn on_asset_reaped(asset_id: &T::AssetId, who: &T::AccountId) -> DispatchResult {
+        if *asset_id != Self::share_asset_id() || !<AccountShares<T>>::exists(who) {
+            return Ok(());
+        }
+
+        let shares = <AccountShares<T>>::take(who);
+        if !shares.is_zero() {
+            <SharesCirculation<T>>::mutate(|v| {
+                *v = v.saturating_sub(shares);
+            });
+        }
+        Ok(())
+    }
+}
+
+/// assets the saving program is running on can't be destroyed
+/// an IOU asset can only be destroyed after its phase no longer references it
+impl<T: Trait> assets::traits::BeforeAssetDestroy<T::AssetId> for Module<T> {
+    fn before_asset_destroy(asset_id: &T::AssetId) -> DispatchResult {
+        ensure!(
+            !<IOUAssetPhaseId<T>>::exists(asset_id),
+            "asset is still the IOU asset of a phase"
+        );
+        ensure!(
+            *asset_id != Self::share_asset_id()
+                && *asset_id != Self::collection_asset_id()
+                && *asset_id != Self::profit_asset_id()
+                && *asset_id != Self::reserved_mint_asset_id(),
+            "asset is used by saving"
+        );
+        Ok(())
+    }
+}
+
 impl<T: Trait> assets::traits::BeforeAssetTransfer<T::AssetId, T::AccountId, T::Balance>
     for Module<T>
 {
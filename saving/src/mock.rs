@@ -262,12 +262,22 @@ impl pallet_generic_asset::Trait for TestRuntime {
     type Balance = u128;
     type AssetId = u32;
 }
+parameter_types! {
+    pub const CreationDeposit: u128 = 0;
+    pub const CreationCooldown: u64 = 0;
+}
 impl assets::Trait for TestRuntime {
     type Event = ();
+    type AdminOrigin = system::EnsureRoot<u64>;
+    type Currency = Balances;
+    type CreationDeposit = CreationDeposit;
+    type CreationCooldown = CreationCooldown;
     type OnAssetMint = SavingTest;
     type OnAssetCreate = ();
     type OnAssetTransfer = SavingTest;
     type OnAssetBurn = SavingTest;
+    type OnAssetReaped = SavingTest;
+    type BeforeAssetDestroy = SavingTest;
     type BeforeAssetMint = ();
     type BeforeAssetCreate = ();
     type BeforeAssetTransfer = SavingTest;
@@ -275,6 +285,7 @@ impl assets::Trait for TestRuntime {
 }
 impl Trait for TestRuntime {
     type Event = ();
+    type AdminOrigin = system::EnsureRoot<u64>;
 }
 
 pub struct ExtBuilder {}
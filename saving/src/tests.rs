@@ -7,8 +7,10 @@ use support::{assert_noop, assert_ok};
 #[allow(unused_imports)]
 use sp_runtime::{
     testing::Header,
-    traits::{BlakeTwo256, Dispatchable, IdentityLookup, OnFinalize, OnInitialize},
-    Perbill,
+    traits::{
+        AccountIdConversion, BlakeTwo256, Dispatchable, IdentityLookup, OnFinalize, OnInitialize,
+    },
+    ModuleId, Perbill,
 };
 
 use crate::mock::{constants::*, new_test_ext, Call, ExtBuilder, Origin, SavingTest, TestRuntime};
@@ -625,6 +627,67 @@ fn share_asset_distribution() {
     });
 }
 
+#[test]
+fn share_dust_is_swept_and_reaped() {
+    ExtBuilder::default().build().execute_with(|| {
+        assert_ok!(SavingTest::create_staking(CHRIS, 10_00000000));
+        assert_ok!(<assets::Module<TestRuntime>>::set_minimum_balance(
+            Origin::ROOT,
+            RBTC_ASSET_ID,
+            1_00000000,
+            assets::DustPolicy::Sweep
+        ));
+        let circulation = SavingTest::shares_circulation();
+
+        assert_ok!(<assets::Module<TestRuntime>>::transfer(
+            Origin::signed(CHRIS),
+            RBTC_ASSET_ID,
+            BOB,
+            2_0000_00000000 - 1
+        ));
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&RBTC_ASSET_ID, &BOB),
+            2_0000_00000000
+        );
+        assert!(!<AccountShares<TestRuntime>>::exists(CHRIS));
+        assert_eq!(SavingTest::account_shares(BOB), 2_0000_00000000);
+        assert_eq!(SavingTest::shares_circulation(), circulation);
+    });
+}
+
+#[test]
+fn iou_asset_destroy_guarded() {
+    ExtBuilder::default().build().execute_with(|| {
+        assert_noop!(
+            <assets::Module<TestRuntime>>::start_destroy(Origin::ROOT, RSC1_ASSET_ID),
+            "asset is still the IOU asset of a phase"
+        );
+        assert_noop!(
+            SavingTest::retire_iou_asset(Origin::ROOT, RSC1_ASSET_ID),
+            "phase is not finished yet"
+        );
+
+        assert_ok!(SavingTest::create_staking(ALICE, PHASE1_QUOTA));
+        assert_eq!(SavingTest::current_phase_id(), PHASE2);
+        assert_noop!(
+            SavingTest::retire_iou_asset(Origin::ROOT, RSC1_ASSET_ID),
+            "iou asset is not fully redeemed"
+        );
+        assert_ok!(<assets::Module<TestRuntime>>::burn(
+            Origin::ROOT,
+            RSC1_ASSET_ID,
+            ALICE,
+            PHASE1_QUOTA
+        ));
+        assert_ok!(SavingTest::retire_iou_asset(Origin::ROOT, RSC1_ASSET_ID));
+        assert_eq!(SavingTest::phase_info(PHASE1).iou_asset_id, None);
+        assert_ok!(<assets::Module<TestRuntime>>::start_destroy(
+            Origin::ROOT,
+            RSC1_ASSET_ID
+        ));
+    });
+}
+
 // this is for future reference
 #[test]
 fn deposit_event_should_work() {
@@ -746,6 +809,101 @@ fn manually_release_bonus() {
     });
 }
 
+#[test]
+fn bonus_uses_share_snapshot() {
+    ExtBuilder::default().build().execute_with(|| {
+        system::Module::<TestRuntime>::set_block_number(1);
+        assert_ok!(<assets::Module<TestRuntime>>::enable_checkpoints(
+            Origin::ROOT,
+            RBTC_ASSET_ID
+        ));
+        assert_ok!(<assets::Module<TestRuntime>>::mint(
+            Origin::ROOT,
+            RBTC_ASSET_ID,
+            DAVE,
+            10000_0000
+        ));
+        assert_ok!(<assets::Module<TestRuntime>>::mint(
+            Origin::ROOT,
+            RBTC_ASSET_ID,
+            BOB,
+            10000_0000
+        ));
+        <LastBonusBlock<TestRuntime>>::put(2);
+
+        // CHRIS buys in right before the bonus
+        system::Module::<TestRuntime>::set_block_number(3);
+        assert_ok!(<assets::Module<TestRuntime>>::mint(
+            Origin::ROOT,
+            RBTC_ASSET_ID,
+            CHRIS,
+            80000_0000
+        ));
+        assert_ok!(<assets::Module<TestRuntime>>::mint(
+            Origin::ROOT,
+            TBD_ASSET_ID,
+            PROFIT_POOL,
+            2400000000,
+        ));
+
+        SavingTest::dispatch_bonus();
+
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&TBD_ASSET_ID, &DAVE),
+            192000000
+        );
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&TBD_ASSET_ID, &BOB),
+            192000000
+        );
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&TBD_ASSET_ID, &CHRIS),
+            0
+        );
+        assert_eq!(SavingTest::last_bonus_block(), 3);
+    });
+}
+
+#[test]
+fn module_accounts_get_no_bonus() {
+    ExtBuilder::default().build().execute_with(|| {
+        let pool: u64 = ModuleId(*b"dfx/amm_").into_sub_account(0);
+        assert_ok!(<assets::Module<TestRuntime>>::enable_holder_index(
+            Origin::ROOT,
+            RBTC_ASSET_ID
+        ));
+        for who in vec![DAVE, BOB, pool] {
+            assert_ok!(<assets::Module<TestRuntime>>::mint(
+                Origin::ROOT,
+                RBTC_ASSET_ID,
+                who,
+                10000_0000
+            ));
+        }
+        assert_ok!(<assets::Module<TestRuntime>>::mint(
+            Origin::ROOT,
+            TBD_ASSET_ID,
+            PROFIT_POOL,
+            2400000000,
+        ));
+
+        SavingTest::dispatch_bonus();
+
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&TBD_ASSET_ID, &DAVE),
+            192000000
+        );
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&TBD_ASSET_ID, &BOB),
+            192000000
+        );
+        assert_eq!(
+            <assets::Module<TestRuntime>>::free_balance(&TBD_ASSET_ID, &pool),
+            0
+        );
+    });
+}
+
 /// this is our ultimate test example
 #[test]
 fn save_12000_sbtc() {